@@ -83,6 +83,7 @@ pub fn make_sample_proofs(config: ChainConfig) -> anyhow::Result<()> {
                 .circuit
                 .supported_aggregated_proof_sizes_with_setup_pow(),
             false,
+            true,
         )?
     };
 
@@ -40,11 +40,11 @@ where
     let prover_options = EnvProverConfig::from_env();
     let prover_config = <PROVER as ProverImpl>::Config::from_env();
     let api_client = api_client_from_env();
-    let prover = PROVER::create_from_config(prover_config);
 
     let _vlog_guard = vlog::init();
 
     vlog::info!("creating prover, worker name: {}", worker_name);
+    let prover = PROVER::create_from_config(prover_config, &worker_name);
 
     // Create client.
 
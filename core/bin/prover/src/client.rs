@@ -10,7 +10,8 @@ use vlog::*;
 // Workspace deps
 use crate::auth_utils::AuthTokenGenerator;
 use zksync_prover_utils::api::{
-    ProverInputRequest, ProverInputResponse, ProverOutputRequest, ProverStopped, WorkingOn,
+    ProverInputRequest, ProverInputResponse, ProverOutputRequest, ProverStopped, ReleaseJob,
+    WorkingOn,
 };
 
 #[derive(Debug, Clone)]
@@ -18,6 +19,7 @@ pub struct ApiClient {
     get_job_url: Url,
     working_on_url: Url,
     publish_url: Url,
+    release_job_url: Url,
     stopped_url: Url,
     // Client keeps connection pool inside, so it is recommended to reuse it (see docstring for reqwest::Client).
     http_client: reqwest::Client,
@@ -40,6 +42,7 @@ impl ApiClient {
             get_job_url: base_url.join("/get_job").unwrap(),
             working_on_url: base_url.join("/working_on").unwrap(),
             publish_url: base_url.join("/publish").unwrap(),
+            release_job_url: base_url.join("/release_job").unwrap(),
             stopped_url: base_url.join("/stopped").unwrap(),
             http_client,
             auth_token_generator,
@@ -172,6 +175,30 @@ impl crate::ApiClient for ApiClient {
         self.with_retries(operation).await
     }
 
+    async fn release_job(&self, job_id: i32, prover_name: &str) -> anyhow::Result<()> {
+        let operation = || async {
+            let response = self
+                .http_client
+                .post(self.release_job_url.clone())
+                .bearer_auth(&self.get_encoded_token()?)
+                .json(&ReleaseJob {
+                    prover_name: prover_name.to_string(),
+                    job_id,
+                })
+                .send()
+                .await
+                .map_err(|e| Transient(format_err!("failed to send release_job request: {}", e)))?;
+
+            if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                return Err(Permanent(format_err!("authorization error")));
+            }
+
+            Ok(())
+        };
+
+        self.with_retries(operation).await
+    }
+
     async fn prover_stopped(&self, prover_name: String) -> anyhow::Result<()> {
         let operation = || async {
             let response = self
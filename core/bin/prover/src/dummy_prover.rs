@@ -28,7 +28,7 @@ pub struct DummyProver {
 impl ProverImpl for DummyProver {
     type Config = DummyProverConfig;
 
-    fn create_from_config(_config: Self::Config) -> Self {
+    fn create_from_config(_config: Self::Config, _prover_name: &str) -> Self {
         Self {
             precomputed_proofs: load_precomputed_proofs()
                 .expect("Failed to load precomputed proofs"),
@@ -11,7 +11,7 @@ use std::sync::{
     atomic::{AtomicBool, AtomicI32, Ordering},
     Arc,
 };
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::oneshot;
 // External deps
 use zksync_crypto::rand::{
@@ -77,12 +77,17 @@ pub trait ProverConfig {
 pub trait ProverImpl {
     /// Config concrete type used by current prover.
     type Config: ProverConfig;
-    /// Creates prover from config and API client.
-    fn create_from_config(config: Self::Config) -> Self;
+    /// Creates prover from config. `prover_name` is used to label the logs of the prover.
+    fn create_from_config(config: Self::Config, prover_name: &str) -> Self;
     fn get_request_aux_data(&self) -> ProverInputRequestAuxData {
         Default::default()
         // TODO: Add the ability to define different config (ZKS-283).
     }
+    /// Checks whether the job can be proven before starting the proof.
+    /// Returns the error the proof creation would fail with.
+    fn check_job(&self, _data: &JobRequestData) -> anyhow::Result<()> {
+        Ok(())
+    }
     /// Resource heavy operation
     fn create_proof(&self, data: JobRequestData) -> anyhow::Result<JobResultData>;
 }
@@ -91,22 +96,25 @@ pub trait ApiClient: Debug {
     async fn get_job(&self, req: ProverInputRequest) -> anyhow::Result<ProverInputResponse>;
     async fn working_on(&self, job_id: i32, prover_name: &str) -> anyhow::Result<()>;
     async fn publish(&self, data: ProverOutputRequest) -> anyhow::Result<()>;
+    async fn release_job(&self, job_id: i32, prover_name: &str) -> anyhow::Result<()>;
     async fn prover_stopped(&self, prover_name: String) -> anyhow::Result<()>;
 }
 
+/// Creates the proof on a separate thread. The prover is given back along with the result,
+/// so that the caller decides what to do if the proof creation fails.
 async fn compute_proof_no_blocking<PROVER>(
     prover: PROVER,
     data: JobRequestData,
-) -> anyhow::Result<(PROVER, JobResultData)>
+) -> anyhow::Result<(PROVER, anyhow::Result<JobResultData>)>
 where
     PROVER: ProverImpl + Send + Sync + 'static,
 {
     let (result_sender, result_receiver) = oneshot::channel();
     std::thread::spawn(move || {
-        let prover_with_proof = prover.create_proof(data).map(|proof| (prover, proof));
-        result_sender.send(prover_with_proof).unwrap_or_default();
+        let proof = prover.create_proof(data);
+        result_sender.send((prover, proof)).unwrap_or_default();
     });
-    result_receiver.await?
+    Ok(result_receiver.await?)
 }
 
 /// Endlessly sends requests to the server, in case of not receiving a response
@@ -128,12 +136,16 @@ async fn heartbeat_future_handle<CLIENT>(
         };
         tokio::time::sleep(timeout_value).await;
 
-        vlog::debug!("Starting sending heartbeats for job with ID: {}", job_id);
+        vlog::debug!(
+            "[{}] Starting sending heartbeats for job with ID: {}",
+            prover_name,
+            job_id
+        );
 
         client
             .working_on(job_id, prover_name)
             .await
-            .map_err(|e| vlog::warn!("Failed to send heartbeat: {}", e))
+            .map_err(|e| vlog::warn!("[{}] Failed to send heartbeat: {}", prover_name, e))
             .unwrap_or_default();
     }
 }
@@ -148,7 +160,7 @@ pub async fn prover_work_cycle<PROVER, CLIENT>(
     CLIENT: 'static + Sync + Send + ApiClient + Clone,
     PROVER: ProverImpl + Send + Sync + 'static,
 {
-    vlog::info!("Running worker cycle");
+    vlog::info!("[{}] Running worker cycle", prover_name);
     let mut new_job_poll_timer = tokio::time::interval(prover_options.prover.cycle_wait());
     loop {
         new_job_poll_timer.tick().await;
@@ -167,7 +179,7 @@ pub async fn prover_work_cycle<PROVER, CLIENT>(
         {
             Ok(job) => job,
             Err(e) => {
-                vlog::warn!("Failed to get job for prover: {}", e);
+                vlog::warn!("[{}] Failed to get job for prover: {}", prover_name, e);
                 continue;
             }
         };
@@ -185,11 +197,30 @@ pub async fn prover_work_cycle<PROVER, CLIENT>(
         };
 
         vlog::info!(
-            "got job id: {}, blocks: [{}, {}]",
+            "[{}] got job id: {}, blocks: [{}, {}]",
+            prover_name,
             job_id,
             first_block,
             last_block
         );
+        if let Err(e) = prover.check_job(&job_data) {
+            // The job is marked as taken by the server, so it's given back to the queue
+            // for the other provers instead of waiting for it to become stale.
+            vlog::error!(
+                "[{}] can't prove job id: {}, blocks: [{}, {}]: {}",
+                prover_name,
+                job_id,
+                first_block,
+                last_block,
+                e
+            );
+            client
+                .release_job(job_id, prover_name)
+                .await
+                .map_err(|e| vlog::warn!("[{}] Failed to release job: {}", prover_name, e))
+                .unwrap_or_default();
+            continue;
+        }
 
         let heartbeat_future_handle = heartbeat_future_handle(
             client.clone(),
@@ -198,12 +229,14 @@ pub async fn prover_work_cycle<PROVER, CLIENT>(
             prover_options.prover.heartbeat_interval(),
         )
         .fuse();
+        let proving_started_at = Instant::now();
         let compute_proof_future = compute_proof_no_blocking(prover, job_data).fuse();
 
         pin_mut!(heartbeat_future_handle, compute_proof_future);
 
         vlog::info!(
-            "starting to compute proof for blocks: [{}, {}]",
+            "[{}] starting to compute proof for blocks: [{}, {}]",
+            prover_name,
             first_block,
             last_block
         );
@@ -215,6 +248,28 @@ pub async fn prover_work_cycle<PROVER, CLIENT>(
             _ = heartbeat_future_handle => unreachable!(),
         };
         prover = ret_prover;
+        let proof = match proof {
+            Ok(proof) => proof,
+            Err(e) => {
+                // The proving thread may still be running (e.g. if the proving timed out)
+                // and compete with the following jobs for memory, so the prover is stopped
+                // to be restarted.
+                vlog::error!(
+                    "[{}] failed to compute proof for blocks: [{}, {}], stopping the prover: {}",
+                    prover_name,
+                    first_block,
+                    last_block,
+                    e
+                );
+                client
+                    .release_job(job_id, prover_name)
+                    .await
+                    .map_err(|e| vlog::warn!("[{}] Failed to release job: {}", prover_name, e))
+                    .unwrap_or_default();
+                return;
+            }
+        };
+        let proving_ms = proving_started_at.elapsed().as_millis() as u64;
 
         client
             .publish(ProverOutputRequest {
@@ -222,13 +277,15 @@ pub async fn prover_work_cycle<PROVER, CLIENT>(
                 first_block,
                 last_block,
                 data: proof,
+                proving_ms,
             })
             .await
-            .map_err(|e| vlog::warn!("Failed to publish proof: {}", e))
+            .map_err(|e| vlog::warn!("[{}] Failed to publish proof: {}", prover_name, e))
             .unwrap_or_default();
 
         vlog::info!(
-            "finished and published proof for blocks: [{}, {}]",
+            "[{}] finished and published proof for blocks: [{}, {}]",
+            prover_name,
             first_block,
             last_block
         );
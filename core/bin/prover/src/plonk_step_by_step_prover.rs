@@ -1,36 +1,137 @@
 // Built-in deps
-use std::sync::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::time::Duration;
 // Workspace deps
+use zksync_circuit::{
+    circuit::ZkSyncCircuit, serialization::ProverData, witness::utils::fr_into_u32_low,
+};
 use zksync_config::ChainConfig;
+use zksync_crypto::franklin_crypto::{bellman::Circuit, circuit::test::TestConstraintSystem};
 use zksync_crypto::proof::{AggregatedProof, PrecomputedSampleProofs, SingleProof};
-use zksync_crypto::Engine;
+use zksync_crypto::{Engine, Fr};
 use zksync_prover_utils::aggregated_proofs::{gen_aggregate_proof, prepare_proof_data};
 use zksync_prover_utils::api::{JobRequestData, JobResultData};
-use zksync_prover_utils::{PlonkVerificationKey, SetupForStepByStepProver};
+use zksync_prover_utils::{InvalidProofError, PlonkVerificationKey, SetupForStepByStepProver};
 use zksync_utils::parse_env;
 // Local deps
 use crate::{ProverConfig, ProverImpl};
 use tokio::time::Instant;
-use zksync_prover_utils::fs_utils::load_precomputed_proofs;
+use zksync_prover_utils::fs_utils::{get_block_verification_key_path, load_precomputed_proofs};
 
 /// We prepare some data before making proof for each block size, so we cache it in case next block
 /// would be of our size
 struct PreparedComputations {
     block_size: usize,
-    setup: SetupForStepByStepProver,
+    setup: Arc<SetupForStepByStepProver>,
+}
+
+/// Number of the latest proofs of each kind used to estimate the proving time.
+const PROVING_TIME_SAMPLES: usize = 8;
+
+/// Kind of the proof created by the prover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProofKind {
+    Block,
+    Aggregated,
+}
+
+impl ProofKind {
+    /// Returns the kind and the size of the proof requested by the job: the block size for
+    /// block proofs and the number of proofs to aggregate for aggregated ones.
+    fn of_job(data: &JobRequestData) -> (Self, usize) {
+        match data {
+            JobRequestData::BlockProof(_, block_size) => (ProofKind::Block, *block_size),
+            JobRequestData::AggregatedBlockProof(proofs) => (ProofKind::Aggregated, proofs.len()),
+        }
+    }
+
+    /// Label of the proof kind in the reported metrics.
+    fn metric_label(self) -> &'static str {
+        match self {
+            ProofKind::Block => "single_proof",
+            ProofKind::Aggregated => "aggregated_proof",
+        }
+    }
+
+    /// Conservative proving time estimate used until the first proof of this kind is created.
+    pub fn default_proving_time(self) -> Duration {
+        match self {
+            ProofKind::Block => Duration::from_secs(10 * 60),
+            ProofKind::Aggregated => Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+/// Snapshot of the prover activity, e.g. for the metrics reporting.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProverStats {
+    /// The largest number of non-noop chunks in a block proven so far.
+    pub peak_used_chunks: usize,
+    /// Number of proofs of each kind created so far.
+    pub proofs_created: HashMap<ProofKind, u64>,
+    /// Proving time of the latest proof of each kind.
+    pub last_proving_time: HashMap<ProofKind, Duration>,
+    /// Number of created block proofs that failed the self-verification.
+    pub verification_failures: u64,
+}
+
+/// Block being proven, as reported if its proof fails the self-verification.
+struct ProvenBlockInfo {
+    block_number: Fr,
+    block_size: usize,
+    used_chunks: usize,
+    public_data_commitment: Fr,
+}
+
+/// Block sizes the prover is able to create proofs for.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProverCapabilities {
+    /// Supported block sizes for which the verification key is available,
+    /// if the created proofs are verified.
+    pub block_sizes: Vec<usize>,
 }
 
 pub struct PlonkStepByStepProver {
     config: PlonkStepByStepProverConfig,
+    /// Name of the prover instance, used to label the logs.
+    prover_name: String,
     prepared_computations: Mutex<Option<PreparedComputations>>,
     precomputed_sample_proofs: PrecomputedSampleProofs,
+    /// The largest number of non-noop chunks in a block proven by this prover.
+    peak_used_chunks: AtomicUsize,
+    /// Proving times of the latest `PROVING_TIME_SAMPLES` proofs of each kind.
+    proving_times: Mutex<HashMap<ProofKind, VecDeque<Duration>>>,
+    /// Number of proofs of each kind created by this prover.
+    proofs_created: Mutex<HashMap<ProofKind, u64>>,
+    /// Number of block proofs created by this prover that failed the self-verification.
+    verification_failures: AtomicU64,
+    /// Verification keys set with `set_verification_key` or `set_parameters`, by block size.
+    verification_keys: RwLock<HashMap<usize, Arc<PlonkVerificationKey>>>,
+    /// Setups set with `set_parameters`, by block size.
+    setups: RwLock<HashMap<usize, Arc<SetupForStepByStepProver>>>,
+    capabilities: RwLock<ProverCapabilities>,
 }
 
 pub struct PlonkStepByStepProverConfig {
     pub all_block_sizes: Vec<usize>,
     pub block_sizes: Vec<usize>,
     pub download_setup_from_network: bool,
+    /// Whether to verify the created proofs before publishing them.
+    pub self_verify: bool,
     pub aggregated_proof_sizes_with_setup_pow: Vec<(usize, u32)>,
+    /// Maximum time to create a single block proof. The proving can't be cancelled, so if it's
+    /// exceeded, an error is returned while the proving thread keeps running in the background.
+    /// No limit if not set.
+    pub proof_timeout: Option<Duration>,
+    /// Whether to write the circuit satisfaction report into `diagnostics_dir` when a block proof
+    /// fails the self-verification. Synthesizing the report is expensive.
+    pub dump_diagnostics_on_failure: bool,
+    /// Directory for the diagnostics files.
+    pub diagnostics_dir: PathBuf,
 }
 
 impl ProverConfig for PlonkStepByStepProverConfig {
@@ -43,19 +144,256 @@ impl ProverConfig for PlonkStepByStepProverConfig {
 
         Self {
             download_setup_from_network: parse_env("MISC_PROVER_DOWNLOAD_SETUP"),
+            self_verify: std::env::var("MISC_PROVER_SELF_VERIFY")
+                .map(|value| value == "true")
+                .unwrap_or(true),
             all_block_sizes: env_config.circuit.supported_block_chunks_sizes,
             block_sizes: env_config.state_keeper.block_chunk_sizes,
             aggregated_proof_sizes_with_setup_pow,
+            proof_timeout: std::env::var("MISC_PROVER_PROOF_TIMEOUT_SEC")
+                .ok()
+                .map(|_| Duration::from_secs(parse_env("MISC_PROVER_PROOF_TIMEOUT_SEC"))),
+            dump_diagnostics_on_failure: std::env::var("MISC_PROVER_DUMP_DIAGNOSTICS_ON_FAILURE")
+                .map(|value| value == "true")
+                .unwrap_or(false),
+            diagnostics_dir: std::env::var("MISC_PROVER_DIAGNOSTICS_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| std::env::temp_dir()),
         }
     }
 }
 
 impl PlonkStepByStepProver {
+    /// Returns the largest number of non-noop chunks in a block successfully proven so far.
+    /// Useful for capacity planning.
+    pub fn peak_used_chunks(&self) -> usize {
+        self.peak_used_chunks.load(Ordering::SeqCst)
+    }
+
+    /// Returns the expected time to create a proof of the given kind: the average over the latest
+    /// proofs of this kind, or a conservative default if no such proofs were created yet.
+    pub fn estimate_proving_time(&self, kind: ProofKind) -> Duration {
+        let proving_times = self.proving_times.lock().unwrap();
+        match proving_times.get(&kind) {
+            Some(samples) if !samples.is_empty() => {
+                samples.iter().sum::<Duration>() / samples.len() as u32
+            }
+            _ => kind.default_proving_time(),
+        }
+    }
+
+    /// Returns the snapshot of the prover activity.
+    pub fn stats(&self) -> ProverStats {
+        let last_proving_time = self
+            .proving_times
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(kind, samples)| samples.back().map(|time| (*kind, *time)))
+            .collect();
+        ProverStats {
+            peak_used_chunks: self.peak_used_chunks(),
+            proofs_created: self.proofs_created.lock().unwrap().clone(),
+            last_proving_time,
+            verification_failures: self.verification_failures.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Returns the block sizes the prover is able to create proofs for.
+    pub fn capabilities(&self) -> ProverCapabilities {
+        self.capabilities.read().unwrap().clone()
+    }
+
+    /// Sets the verification key used to verify the created proofs for blocks of the given size,
+    /// instead of the one read from the keys directory. Allows to check a new key (e.g. after a key
+    /// rotation) by re-proving blocks with it before switching to it.
+    pub fn set_verification_key(&self, block_size: usize, vk: PlonkVerificationKey) {
+        // Drop the cached setup, so that it's prepared again for the next proof.
+        self.prepared_computations.lock().unwrap().take();
+        self.verification_keys
+            .write()
+            .unwrap()
+            .insert(block_size, Arc::new(vk));
+        let mut capabilities = self.capabilities.write().unwrap();
+        if !capabilities.block_sizes.contains(&block_size) {
+            capabilities.block_sizes.push(block_size);
+            capabilities.block_sizes.sort_unstable();
+        }
+    }
+
+    /// Sets the setup used to prove blocks of the given size instead of the one prepared from
+    /// the universal setup in the keys directory, along with the verification key generated for it.
+    /// Allows to check a new parameter set (e.g. after a trusted setup ceremony) by re-proving
+    /// blocks with it before switching to it.
+    pub fn set_parameters(
+        &self,
+        block_size: usize,
+        setup: SetupForStepByStepProver,
+    ) -> anyhow::Result<()> {
+        let vk = setup.make_verification_key()?;
+        self.setups
+            .write()
+            .unwrap()
+            .insert(block_size, Arc::new(setup));
+        self.set_verification_key(block_size, vk);
+        Ok(())
+    }
+
+    /// Checks whether the prover would accept the job of the given kind without creating the proof.
+    /// `size` is the block size for block proofs and the number of proofs to aggregate for
+    /// aggregated ones. Returns the error the proof creation would fail with.
+    pub fn can_accept(&self, kind: ProofKind, size: usize) -> anyhow::Result<()> {
+        match kind {
+            ProofKind::Block => self.check_block_size(size),
+            ProofKind::Aggregated => self.aggregate_size(size).map(drop),
+        }
+    }
+
+    fn check_block_size(&self, block_size: usize) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.config.all_block_sizes.contains(&block_size),
+            "Unsupported block size: {}, supported sizes: {:?}",
+            block_size,
+            self.config.all_block_sizes
+        );
+        anyhow::ensure!(
+            self.capabilities
+                .read()
+                .unwrap()
+                .block_sizes
+                .contains(&block_size),
+            "Verification key for block size {} is missing",
+            block_size
+        );
+        Ok(())
+    }
+
+    /// Returns the smallest supported aggregated proof size fitting the given number of proofs.
+    fn aggregate_size(&self, proofs_count: usize) -> anyhow::Result<usize> {
+        self.config
+            .aggregated_proof_sizes_with_setup_pow
+            .iter()
+            .map(|(aggregate_size, _)| *aggregate_size)
+            .find(|aggregate_size| *aggregate_size >= proofs_count)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Failed to find aggregate proof size to fit all proofs, size: {:?}, proofs: {}",
+                    self.config.aggregated_proof_sizes_with_setup_pow,
+                    proofs_count
+                )
+            })
+    }
+
+    /// Returns the key to verify the created proofs for blocks of the given size with,
+    /// or `None` if the proofs are not verified.
+    fn verification_key(
+        &self,
+        block_size: usize,
+    ) -> anyhow::Result<Option<Arc<PlonkVerificationKey>>> {
+        if !self.config.self_verify {
+            return Ok(None);
+        }
+        let custom_vk = self
+            .verification_keys
+            .read()
+            .unwrap()
+            .get(&block_size)
+            .cloned();
+        let vk = match custom_vk {
+            Some(vk) => vk,
+            None => {
+                Arc::new(PlonkVerificationKey::read_verification_key_for_main_circuit(block_size)?)
+            }
+        };
+        Ok(Some(vk))
+    }
+
+    /// Updates the peak number of used chunks with the block that was successfully proven.
+    fn record_used_chunks(&self, used_chunks: usize) {
+        self.peak_used_chunks
+            .fetch_max(used_chunks, Ordering::SeqCst);
+    }
+
+    fn record_proving_time(&self, kind: ProofKind, proving_time: Duration) {
+        *self.proofs_created.lock().unwrap().entry(kind).or_default() += 1;
+        let mut proving_times = self.proving_times.lock().unwrap();
+        let samples = proving_times.entry(kind).or_default();
+        if samples.len() == PROVING_TIME_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(proving_time);
+    }
+
+    /// Reports the snapshot of the prover activity and the proving time estimates as metrics.
+    fn report_stats(&self) {
+        let stats = self.stats();
+        metrics::gauge!("prover.peak_used_chunks", stats.peak_used_chunks as f64);
+        metrics::gauge!(
+            "prover.verification_failures",
+            stats.verification_failures as f64
+        );
+        for kind in [ProofKind::Block, ProofKind::Aggregated].iter().copied() {
+            let label = kind.metric_label();
+            let proofs_created = stats.proofs_created.get(&kind).copied().unwrap_or_default();
+            metrics::gauge!("prover.proofs_created", proofs_created as f64, "type" => label);
+            if let Some(time) = stats.last_proving_time.get(&kind) {
+                metrics::gauge!("prover.last_proving_time", time.as_secs_f64(), "type" => label);
+            }
+            metrics::gauge!(
+                "prover.estimated_proving_time",
+                self.estimate_proving_time(kind).as_secs_f64(),
+                "type" => label
+            );
+        }
+    }
+
+    /// Counts and reports the block proof that failed the self-verification, and writes
+    /// the block diagnostics if `diagnostics_data` is provided. Other errors are left as is.
+    fn handle_block_proof_error(
+        &self,
+        error: &anyhow::Error,
+        block: &ProvenBlockInfo,
+        diagnostics_data: Option<&ProverData>,
+    ) {
+        if error.downcast_ref::<InvalidProofError>().is_none() {
+            return;
+        }
+
+        self.verification_failures.fetch_add(1, Ordering::SeqCst);
+        self.report_stats();
+        vlog::error!(
+            "[{}] Block proof failed the self-verification, block number: {}, block size: {}, used chunks: {}, public data commitment: {}",
+            self.prover_name,
+            block.block_number,
+            block.block_size,
+            block.used_chunks,
+            block.public_data_commitment
+        );
+        if let Some(prover_data) = diagnostics_data {
+            match dump_diagnostics(&self.config.diagnostics_dir, prover_data) {
+                Ok(path) => vlog::info!(
+                    "[{}] Diagnostics of block {} written to {}",
+                    self.prover_name,
+                    block.block_number,
+                    path.display()
+                ),
+                Err(err) => vlog::error!(
+                    "[{}] Failed to write diagnostics of block {}: {}",
+                    self.prover_name,
+                    block.block_number,
+                    err
+                ),
+            }
+        }
+    }
+
     fn create_single_block_proof(
         &self,
-        witness: zksync_circuit::circuit::ZkSyncCircuit<'_, Engine>,
+        witness: ZkSyncCircuit<'static, Engine>,
         block_size: usize,
     ) -> anyhow::Result<SingleProof> {
+        self.check_block_size(block_size)?;
+
         let valid_cached_precomp = {
             self.prepared_computations
                 .lock()
@@ -64,24 +402,51 @@ impl PlonkStepByStepProver {
                 .filter(|p| p.block_size == block_size)
         };
 
-        let precomp = if let Some(precomp) = valid_cached_precomp {
+        let custom_setup = self.setups.read().unwrap().get(&block_size).cloned();
+        let precomp = if let Some(setup) = custom_setup {
+            PreparedComputations { block_size, setup }
+        } else if let Some(precomp) = valid_cached_precomp {
             precomp
         } else {
             let setup = SetupForStepByStepProver::prepare_setup_for_step_by_step_prover(
                 witness.clone(),
                 self.config.download_setup_from_network,
             )?;
-            PreparedComputations { block_size, setup }
+            PreparedComputations {
+                block_size,
+                setup: Arc::new(setup),
+            }
         };
 
-        let vk = PlonkVerificationKey::read_verification_key_for_main_circuit(block_size)?;
-        let verified_proof = precomp
-            .setup
-            .gen_step_by_step_proof_using_prepared_setup(witness, &vk)?;
+        let vk = self.verification_key(block_size)?;
+
+        let (proof, precomp) = match self.config.proof_timeout {
+            Some(timeout) => {
+                let proving = move || {
+                    let proof = gen_single_block_proof(&precomp.setup, witness, vk.as_deref());
+                    (proof, precomp)
+                };
+                match run_with_timeout(timeout, proving) {
+                    Ok(result) => result,
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        // The proving can't be cancelled, so the proving thread is abandoned.
+                        anyhow::bail!("proving timed out after {:?}", timeout)
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        anyhow::bail!("proving thread panicked")
+                    }
+                }
+            }
+            None => {
+                let proof = gen_single_block_proof(&precomp.setup, witness, vk.as_deref());
+                (proof, precomp)
+            }
+        };
+        let proof = proof?;
 
         *self.prepared_computations.lock().unwrap() = Some(precomp);
 
-        Ok(verified_proof)
+        Ok(proof)
     }
 
     fn create_aggregated_block_proof(
@@ -94,19 +459,15 @@ impl PlonkStepByStepProver {
             self.prepared_computations.lock().unwrap().take();
         }
 
-        let proofs_to_pad = {
-            let aggregate_size = self.config.aggregated_proof_sizes_with_setup_pow.iter().find(|(aggregate_size, _)| aggregate_size >= &proofs.len())
-                .ok_or_else(|| {
-                    anyhow::anyhow!("Failed to find aggregate proof size to fit all proofs, size: {:?}, proofs: {}", self.config.aggregated_proof_sizes_with_setup_pow, proofs.len())
-                })?.0;
-            aggregate_size
-                .checked_sub(proofs.len())
-                .expect("Aggregate size should be <= number of proofs")
-        };
+        let proofs_to_pad = self
+            .aggregate_size(proofs.len())?
+            .checked_sub(proofs.len())
+            .expect("Aggregate size should be <= number of proofs");
 
         if proofs_to_pad > 0 {
             vlog::info!(
-                "Padding aggregated proofs. proofs: {}, proofs to pad: {}, aggregate_size: {}",
+                "[{}] Padding aggregated proofs. proofs: {}, proofs to pad: {}, aggregate_size: {}",
+                self.prover_name,
                 proofs.len(),
                 proofs_to_pad,
                 proofs.len() + proofs_to_pad
@@ -131,14 +492,101 @@ impl PlonkStepByStepProver {
             proof_data,
             &self.config.aggregated_proof_sizes_with_setup_pow,
             self.config.download_setup_from_network,
+            self.config.self_verify,
         )
     }
 }
 
+/// Synthesizes the block circuit into the test constraint system and writes the satisfaction
+/// report along with the circuit inputs and the block public data into the diagnostics file
+/// named by the block number. Returns the path of the written file.
+pub fn dump_diagnostics(dir: &Path, prover_data: &ProverData) -> anyhow::Result<PathBuf> {
+    let mut cs = TestConstraintSystem::<Engine>::new();
+    prover_data.clone().into_circuit().synthesize(&mut cs)?;
+
+    let mut report = String::new();
+    writeln!(report, "[satisfaction]")?;
+    writeln!(report, "satisfied: {}", cs.is_satisfied())?;
+    writeln!(
+        report,
+        "unsatisfied constraint: {}",
+        cs.which_is_unsatisfied().unwrap_or("none")
+    )?;
+    writeln!(report, "constraints: {}", cs.num_constraints())?;
+    writeln!(report, "\n[unconstrained]")?;
+    writeln!(report, "{}", cs.find_unconstrained())?;
+    writeln!(report, "\n[inputs]")?;
+    writeln!(report, "block number: {}", prover_data.block_number)?;
+    writeln!(report, "block timestamp: {}", prover_data.block_timestamp)?;
+    writeln!(
+        report,
+        "validator address: {}",
+        prover_data.validator_address
+    )?;
+    writeln!(report, "old root: {}", prover_data.old_root)?;
+    writeln!(report, "new root: {}", prover_data.new_root)?;
+    writeln!(
+        report,
+        "public data commitment: {}",
+        prover_data.public_data_commitment
+    )?;
+    writeln!(report, "\n[public data]")?;
+    for (chunk, operation) in prover_data.operations.iter().enumerate() {
+        writeln!(report, "{}: {:?}", chunk, operation.pubdata_chunk)?;
+    }
+
+    let path = dir.join(format!(
+        "block_{}_diagnostics.txt",
+        fr_into_u32_low(prover_data.block_number)
+    ));
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// Runs `f` on a separate thread and waits for its result for at most `timeout`.
+/// The thread is left running if the time is out.
+fn run_with_timeout<T, F>(timeout: Duration, f: F) -> Result<T, mpsc::RecvTimeoutError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        // The receiver is dropped if the time is out.
+        sender.send(f()).ok();
+    });
+    receiver.recv_timeout(timeout)
+}
+
+fn gen_single_block_proof(
+    setup: &SetupForStepByStepProver,
+    witness: ZkSyncCircuit<'static, Engine>,
+    vk: Option<&PlonkVerificationKey>,
+) -> anyhow::Result<SingleProof> {
+    match vk {
+        Some(vk) => setup.gen_step_by_step_proof_using_prepared_setup(witness, vk),
+        None => setup.gen_unverified_step_by_step_proof_using_prepared_setup(witness),
+    }
+}
+
 impl ProverImpl for PlonkStepByStepProver {
     type Config = PlonkStepByStepProverConfig;
 
+    fn check_job(&self, data: &JobRequestData) -> anyhow::Result<()> {
+        let (kind, size) = ProofKind::of_job(data);
+        self.can_accept(kind, size)?;
+        vlog::info!(
+            "[{}] Expected proving time: {:?}, proof kind: {:?}, size: {}",
+            self.prover_name,
+            self.estimate_proving_time(kind),
+            kind,
+            size
+        );
+        Ok(())
+    }
+
     fn create_proof(&self, data: JobRequestData) -> Result<JobResultData, anyhow::Error> {
+        let start = Instant::now();
         let proof = match data {
             JobRequestData::AggregatedBlockProof(proofs_to_aggregate) => {
                 let block_sizes = proofs_to_aggregate
@@ -149,22 +597,38 @@ impl ProverImpl for PlonkStepByStepProver {
                 let aggregate_proof = self.create_aggregated_block_proof(proofs_to_aggregate).map_err(|e| {
                     anyhow::format_err!("Failed to aggregate block proofs, num proofs: {}, block sizes: {:?}, err {}", block_sizes.len(), &block_sizes, e)
                 })?;
+                self.record_proving_time(ProofKind::Aggregated, start.elapsed());
+                self.report_stats();
 
                 JobResultData::AggregatedBlockProof(aggregate_proof)
             }
             JobRequestData::BlockProof(zksync_circuit, block_size) => {
-                let start = Instant::now();
+                let block = ProvenBlockInfo {
+                    block_number: zksync_circuit.block_number,
+                    block_size,
+                    used_chunks: zksync_circuit.used_chunks(),
+                    public_data_commitment: zksync_circuit.public_data_commitment,
+                };
+                let diagnostics_data = if self.config.dump_diagnostics_on_failure {
+                    Some(zksync_circuit.clone())
+                } else {
+                    None
+                };
                 let zksync_circuit = zksync_circuit.into_circuit();
                 metrics::histogram!("prover", start.elapsed(), "stage" => "prepare_proof", "type" => "single_proof");
                 let proof = self
                     .create_single_block_proof(zksync_circuit, block_size)
                     .map_err(|e| {
+                        self.handle_block_proof_error(&e, &block, diagnostics_data.as_ref());
                         anyhow::format_err!(
                             "Failed to create single block proof, block size: {}, err: {}",
                             block_size,
                             e
                         )
                     })?;
+                self.record_used_chunks(block.used_chunks);
+                self.record_proving_time(ProofKind::Block, start.elapsed());
+                self.report_stats();
 
                 JobResultData::BlockProof(proof)
             }
@@ -173,13 +637,363 @@ impl ProverImpl for PlonkStepByStepProver {
         Ok(proof)
     }
 
-    fn create_from_config(config: PlonkStepByStepProverConfig) -> Self {
+    fn create_from_config(config: PlonkStepByStepProverConfig, prover_name: &str) -> Self {
         assert!(!config.block_sizes.is_empty());
+        // Verification keys are read for each proof, so the missing ones only prevent
+        // proving blocks of the corresponding sizes.
+        let block_sizes = config
+            .all_block_sizes
+            .iter()
+            .copied()
+            .filter(|&block_size| {
+                let available =
+                    !config.self_verify || get_block_verification_key_path(block_size).exists();
+                if !available {
+                    vlog::warn!(
+                        "[{}] Verification key for block size {} is missing, blocks of this size won't be proven",
+                        prover_name,
+                        block_size
+                    );
+                }
+                available
+            })
+            .collect();
         PlonkStepByStepProver {
             config,
+            prover_name: prover_name.to_string(),
             prepared_computations: Mutex::new(None),
             precomputed_sample_proofs: load_precomputed_proofs()
                 .expect("Failed to load precomputed sample proofs"),
+            peak_used_chunks: AtomicUsize::new(0),
+            proving_times: Mutex::new(HashMap::new()),
+            proofs_created: Mutex::new(HashMap::new()),
+            verification_failures: AtomicU64::new(0),
+            verification_keys: RwLock::new(HashMap::new()),
+            setups: RwLock::new(HashMap::new()),
+            capabilities: RwLock::new(ProverCapabilities { block_sizes }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_crypto::ff::Field;
+    use zksync_prover_utils::test_data::empty_block_circuit;
+
+    const TEST_PROVER_NAME: &str = "test_prover";
+
+    fn test_config() -> PlonkStepByStepProverConfig {
+        PlonkStepByStepProverConfig {
+            all_block_sizes: vec![10],
+            block_sizes: vec![10],
+            download_setup_from_network: false,
+            self_verify: false,
+            aggregated_proof_sizes_with_setup_pow: vec![(1, 22)],
+            proof_timeout: None,
+            dump_diagnostics_on_failure: false,
+            diagnostics_dir: std::env::temp_dir(),
+        }
+    }
+
+    fn test_prover() -> PlonkStepByStepProver {
+        PlonkStepByStepProver::create_from_config(test_config(), TEST_PROVER_NAME)
+    }
+
+    #[test]
+    fn test_record_proving_time() {
+        let prover = test_prover();
+        assert_eq!(
+            prover.estimate_proving_time(ProofKind::Block),
+            ProofKind::Block.default_proving_time()
+        );
+
+        prover.record_proving_time(ProofKind::Block, Duration::from_secs(10));
+        prover.record_proving_time(ProofKind::Block, Duration::from_secs(20));
+        assert_eq!(
+            prover.estimate_proving_time(ProofKind::Block),
+            Duration::from_secs(15)
+        );
+        assert_eq!(
+            prover.estimate_proving_time(ProofKind::Aggregated),
+            ProofKind::Aggregated.default_proving_time()
+        );
+
+        // Only the latest samples are averaged.
+        for _ in 0..PROVING_TIME_SAMPLES {
+            prover.record_proving_time(ProofKind::Block, Duration::from_secs(40));
         }
+        assert_eq!(
+            prover.estimate_proving_time(ProofKind::Block),
+            Duration::from_secs(40)
+        );
+
+        let stats = prover.stats();
+        assert_eq!(
+            stats.proofs_created.get(&ProofKind::Block),
+            Some(&(PROVING_TIME_SAMPLES as u64 + 2))
+        );
+        assert_eq!(
+            stats.last_proving_time.get(&ProofKind::Block),
+            Some(&Duration::from_secs(40))
+        );
+        assert!(!stats.last_proving_time.contains_key(&ProofKind::Aggregated));
+    }
+
+    #[test]
+    fn test_record_used_chunks() {
+        let prover = test_prover();
+        assert_eq!(prover.peak_used_chunks(), 0);
+
+        prover.record_used_chunks(12);
+        assert_eq!(prover.peak_used_chunks(), 12);
+        // A smaller block doesn't lower the peak.
+        prover.record_used_chunks(6);
+        assert_eq!(prover.peak_used_chunks(), 12);
+        prover.record_used_chunks(18);
+        assert_eq!(prover.peak_used_chunks(), 18);
+    }
+
+    #[test]
+    fn test_prover_stats() {
+        let prover = test_prover();
+        assert_eq!(prover.stats(), ProverStats::default());
+
+        prover.record_used_chunks(12);
+        prover.record_proving_time(ProofKind::Block, Duration::from_secs(10));
+        prover.record_used_chunks(6);
+        prover.record_proving_time(ProofKind::Block, Duration::from_secs(20));
+        prover.record_proving_time(ProofKind::Aggregated, Duration::from_secs(30));
+        prover.handle_block_proof_error(
+            &anyhow::Error::new(InvalidProofError),
+            &ProvenBlockInfo {
+                block_number: Fr::one(),
+                block_size: 10,
+                used_chunks: 18,
+                public_data_commitment: Fr::zero(),
+            },
+            None,
+        );
+
+        let stats = prover.stats();
+        // The failed proof doesn't affect the peak.
+        assert_eq!(stats.peak_used_chunks, 12);
+        assert_eq!(stats.proofs_created.get(&ProofKind::Block), Some(&2));
+        assert_eq!(stats.proofs_created.get(&ProofKind::Aggregated), Some(&1));
+        assert_eq!(
+            stats.last_proving_time.get(&ProofKind::Block),
+            Some(&Duration::from_secs(20))
+        );
+        assert_eq!(
+            stats.last_proving_time.get(&ProofKind::Aggregated),
+            Some(&Duration::from_secs(30))
+        );
+        assert_eq!(stats.verification_failures, 1);
+    }
+
+    #[test]
+    fn test_handle_block_proof_error() {
+        let prover = test_prover();
+        let block = ProvenBlockInfo {
+            block_number: Fr::one(),
+            block_size: 10,
+            used_chunks: 6,
+            public_data_commitment: Fr::zero(),
+        };
+
+        // Only the self-verification failures are counted.
+        prover.handle_block_proof_error(&anyhow::anyhow!("proving failed"), &block, None);
+        assert_eq!(prover.stats().verification_failures, 0);
+
+        let error = anyhow::Error::new(InvalidProofError);
+        prover.handle_block_proof_error(&error, &block, None);
+        prover.handle_block_proof_error(&error, &block, None);
+        assert_eq!(prover.stats().verification_failures, 2);
+        assert!(prover.stats().proofs_created.is_empty());
+    }
+
+    #[test]
+    fn test_check_job_size() {
+        let prover = test_prover();
+        prover
+            .check_block_size(10)
+            .expect("Supported block size must be accepted");
+        let err = prover
+            .check_block_size(32)
+            .expect_err("Unsupported block size must be rejected");
+        assert!(err.to_string().contains("Unsupported block size: 32"));
+
+        assert_eq!(prover.aggregate_size(1).unwrap(), 1);
+        let err = prover
+            .aggregate_size(2)
+            .expect_err("Too many proofs to aggregate must be rejected");
+        assert!(err
+            .to_string()
+            .contains("Failed to find aggregate proof size to fit all proofs"));
+    }
+
+    #[test]
+    fn test_set_verification_key() {
+        let prover = test_prover();
+        let stored_key_size = |block_size| {
+            prover
+                .verification_keys
+                .read()
+                .unwrap()
+                .get(&block_size)
+                .map(|vk| vk.0.n)
+        };
+        let vk_10 = PlonkVerificationKey::read_verification_key_for_main_circuit(10)
+            .expect("Failed to read verification key");
+        let vk_32 = PlonkVerificationKey::read_verification_key_for_main_circuit(32)
+            .expect("Failed to read verification key");
+        let (vk_10_size, vk_32_size) = (vk_10.0.n, vk_32.0.n);
+        assert_ne!(vk_10_size, vk_32_size);
+
+        prover.set_verification_key(10, vk_10);
+        assert_eq!(stored_key_size(10), Some(vk_10_size));
+        // The key set for the same block size replaces the stored one.
+        prover.set_verification_key(10, vk_32);
+        assert_eq!(stored_key_size(10), Some(vk_32_size));
+        assert_eq!(prover.capabilities().block_sizes, vec![10]);
+    }
+
+    #[test]
+    fn test_set_parameters() {
+        let prover = test_prover();
+        let vk_32 = PlonkVerificationKey::read_verification_key_for_main_circuit(32)
+            .expect("Failed to read verification key");
+        prover.set_verification_key(10, vk_32);
+
+        let setup = SetupForStepByStepProver::prepare_setup_for_step_by_step_prover(
+            empty_block_circuit(),
+            false,
+        )
+        .expect("Failed to prepare setup");
+        let vk_size = setup
+            .make_verification_key()
+            .expect("Failed to generate verification key")
+            .0
+            .n;
+        prover
+            .set_parameters(10, setup)
+            .expect("Failed to set parameters");
+
+        // The verification key generated for the setup replaces the stored one.
+        let stored_vk_size = prover.verification_keys.read().unwrap()[&10].0.n;
+        assert_eq!(stored_vk_size, vk_size);
+        assert!(prover.setups.read().unwrap().contains_key(&10));
+        assert!(!prover.setups.read().unwrap().contains_key(&32));
+    }
+
+    #[test]
+    fn test_verification_key() {
+        // The proofs are not verified, so no key is needed even if it's missing.
+        let prover = test_prover();
+        assert!(prover.verification_key(7).unwrap().is_none());
+
+        let mut config = test_config();
+        config.all_block_sizes = vec![7, 10];
+        config.self_verify = true;
+        let prover = PlonkStepByStepProver::create_from_config(config, TEST_PROVER_NAME);
+        prover
+            .verification_key(7)
+            .expect_err("Missing verification key must be reported");
+        let vk_10 = PlonkVerificationKey::read_verification_key_for_main_circuit(10)
+            .expect("Failed to read verification key");
+        let vk_10_size = vk_10.0.n;
+        let vk = prover
+            .verification_key(10)
+            .unwrap()
+            .expect("Key must be read");
+        assert_eq!(vk.0.n, vk_10_size);
+
+        prover.set_verification_key(7, vk_10);
+        let vk = prover
+            .verification_key(7)
+            .unwrap()
+            .expect("Key must be set");
+        assert_eq!(vk.0.n, vk_10_size);
+    }
+
+    /// Returns the logs written while running `f`.
+    fn capture_logs(f: impl FnOnce()) -> String {
+        #[derive(Clone, Default)]
+        struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for LogBuffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = LogBuffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .with_max_level(tracing::Level::INFO)
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+
+        let logs = buffer.0.lock().unwrap().clone();
+        String::from_utf8(logs).unwrap()
+    }
+
+    #[test]
+    fn test_logs_are_labelled() {
+        let logs = capture_logs(|| {
+            // There is no verification key for this block size.
+            let mut config = test_config();
+            config.all_block_sizes = vec![7, 10];
+            config.self_verify = true;
+            let prover = PlonkStepByStepProver::create_from_config(config, TEST_PROVER_NAME);
+
+            prover
+                .check_job(&JobRequestData::AggregatedBlockProof(Vec::new()))
+                .expect("Aggregated proof job must be accepted");
+            prover.handle_block_proof_error(
+                &anyhow::Error::new(InvalidProofError),
+                &ProvenBlockInfo {
+                    block_number: Fr::one(),
+                    block_size: 10,
+                    used_chunks: 6,
+                    public_data_commitment: Fr::zero(),
+                },
+                None,
+            );
+        });
+
+        for message in &[
+            "Verification key for block size 7 is missing",
+            "Expected proving time",
+            "Block proof failed the self-verification",
+        ] {
+            let labelled_message = format!("[{}] {}", TEST_PROVER_NAME, message);
+            assert!(logs.contains(&labelled_message), "Missing log: {}", message);
+        }
+    }
+
+    #[test]
+    fn test_run_with_timeout() {
+        assert_eq!(run_with_timeout(Duration::from_secs(10), || 42), Ok(42));
+
+        // The function is blocked until the sender is dropped, so the time is out.
+        let (sender, receiver) = mpsc::channel::<()>();
+        assert_eq!(
+            run_with_timeout(Duration::from_millis(1), move || receiver.recv()),
+            Err(mpsc::RecvTimeoutError::Timeout)
+        );
+        drop(sender);
+
+        assert_eq!(
+            run_with_timeout::<(), _>(Duration::from_secs(10), || panic!("proving failed")),
+            Err(mpsc::RecvTimeoutError::Disconnected)
+        );
     }
 }
@@ -19,15 +19,18 @@ use zksync_crypto::{
 };
 use zksync_prover::dummy_prover::{DummyProver, DummyProverConfig};
 use zksync_prover::plonk_step_by_step_prover::{
-    PlonkStepByStepProver, PlonkStepByStepProverConfig,
+    dump_diagnostics, PlonkStepByStepProver, PlonkStepByStepProverConfig, ProofKind,
+    ProverCapabilities,
 };
 use zksync_prover::{ProverImpl, ShutdownRequest};
 use zksync_prover_utils::api::{
-    JobRequestData, ProverInputRequest, ProverInputResponse, ProverOutputRequest,
+    JobRequestData, JobResultData, ProverInputRequest, ProverInputResponse, ProverOutputRequest,
 };
+use zksync_prover_utils::PlonkVerificationKey;
 use zksync_types::{
-    block::smallest_block_size_for_chunks, operations::DepositOp, Account, AccountId, Address,
-    BlockNumber, Deposit, TokenId,
+    block::smallest_block_size_for_chunks,
+    operations::{DepositOp, WithdrawOp},
+    Account, AccountId, Address, BlockNumber, Deposit, TokenId,
 };
 
 /// Set of different parameters needed for the prover to work
@@ -47,6 +50,10 @@ impl Default for MockProverConfigs {
             aggregated_proof_sizes_with_setup_pow: vec![(1, 22), (4, 23), (8, 24), (18, 25)],
             block_sizes: vec![10, 32],
             download_setup_from_network: false,
+            self_verify: true,
+            proof_timeout: None,
+            dump_diagnostics_on_failure: false,
+            diagnostics_dir: std::env::temp_dir(),
         };
         let dummy_config = DummyProverConfig {
             block_sizes: vec![10, 32],
@@ -79,6 +86,11 @@ impl Default for MockProverConfigs {
 }
 
 fn test_data_for_prover() -> JobRequestData {
+    test_data_for_prover_with_deposits(1)
+}
+
+/// Creates the block proof job data for the block with the given number of deposits.
+fn test_data_for_prover_with_deposits(deposits_count: u32) -> JobRequestData {
     let mut circuit_account_tree =
         CircuitAccountTree::new(zksync_crypto::params::account_tree_depth());
     let fee_account_id = AccountId(0);
@@ -90,32 +102,35 @@ fn test_data_for_prover() -> JobRequestData {
     let mut witness_accum =
         WitnessBuilder::new(&mut circuit_account_tree, fee_account_id, BlockNumber(1), 0);
 
-    let empty_account_id = AccountId(1);
-    let empty_account_address = [7u8; 20].into();
-    let deposit_op = DepositOp {
-        priority_op: Deposit {
-            from: empty_account_address,
-            token: TokenId(0),
-            amount: BigUint::from(1u32),
-            to: empty_account_address,
-        },
-        account_id: empty_account_id,
-    };
+    for id in 1..=deposits_count {
+        let empty_account_id = AccountId(id);
+        let empty_account_address = [id as u8; 20].into();
+        let deposit_op = DepositOp {
+            priority_op: Deposit {
+                from: empty_account_address,
+                token: TokenId(0),
+                amount: BigUint::from(1u32),
+                to: empty_account_address,
+            },
+            account_id: empty_account_id,
+        };
 
-    let deposit_witness = DepositWitness::apply_tx(witness_accum.account_tree, &deposit_op);
-    let deposit_operations = deposit_witness.calculate_operations(());
-    let pub_data_from_witness = deposit_witness.get_pubdata();
-    let offset_commitment = deposit_witness.get_offset_commitment_data();
+        let deposit_witness = DepositWitness::apply_tx(witness_accum.account_tree, &deposit_op);
+        let deposit_operations = deposit_witness.calculate_operations(());
+        let pub_data_from_witness = deposit_witness.get_pubdata();
+        let offset_commitment = deposit_witness.get_offset_commitment_data();
 
-    witness_accum.add_operation_with_pubdata(
-        deposit_operations,
-        pub_data_from_witness,
-        offset_commitment,
-    );
-    witness_accum.extend_pubdata_with_noops(smallest_block_size_for_chunks(
-        DepositOp::CHUNKS,
+        witness_accum.add_operation_with_pubdata(
+            deposit_operations,
+            pub_data_from_witness,
+            offset_commitment,
+        );
+    }
+    let block_size = smallest_block_size_for_chunks(
+        DepositOp::CHUNKS * deposits_count as usize,
         &ChainConfig::from_env().circuit.supported_block_chunks_sizes,
-    ));
+    );
+    witness_accum.extend_pubdata_with_noops(block_size);
     witness_accum.collect_fees(&Vec::new());
     witness_accum.calculate_pubdata_commitment();
 
@@ -142,7 +157,7 @@ fn test_data_for_prover() -> JobRequestData {
             .unwrap(),
     };
 
-    JobRequestData::BlockProof(prover_data, 10)
+    JobRequestData::BlockProof(prover_data, block_size)
 }
 
 #[tokio::test]
@@ -155,7 +170,7 @@ async fn test_shutdown_request() {
         prover_name,
     } = MockProverConfigs::default();
 
-    let prover = PlonkStepByStepProver::create_from_config(plonk_config);
+    let prover = PlonkStepByStepProver::create_from_config(plonk_config, &prover_name);
     let client = MockApiClient::default();
 
     let prover_work_cycle = zksync_prover::prover_work_cycle(
@@ -193,7 +208,7 @@ async fn test_receiving_heartbeats() {
         prover_name,
     } = MockProverConfigs::default();
 
-    let prover = PlonkStepByStepProver::create_from_config(plonk_config);
+    let prover = PlonkStepByStepProver::create_from_config(plonk_config, &prover_name);
     let client = MockApiClient::default();
 
     let prover_work_cycle = zksync_prover::prover_work_cycle(
@@ -230,7 +245,7 @@ async fn test_publishing_proof() {
         prover_name,
     } = MockProverConfigs::default();
 
-    let prover = DummyProver::create_from_config(dummy_config);
+    let prover = DummyProver::create_from_config(dummy_config, &prover_name);
     let client = MockApiClient::default();
 
     let prover_work_cycle = zksync_prover::prover_work_cycle(
@@ -249,9 +264,59 @@ async fn test_publishing_proof() {
         _ = prover_work_cycle => panic!("prover work ended too quickly"),
         _ = timeout => {
             shutdown_request.set();
-            assert!(
-                client.published_prof.lock().await.get(&0).cloned().is_some()
+            let published = client
+                .published_prof
+                .lock()
+                .await
+                .get(&0)
+                .cloned()
+                .expect("proof was not published");
+            assert_eq!(published.job_id, 0);
+            assert_eq!(published.first_block, BlockNumber(1));
+            assert_eq!(published.last_block, BlockNumber(1));
+            assert!(matches!(published.data, JobResultData::BlockProof(_)));
+        },
+    };
+}
+
+#[tokio::test]
+async fn test_releasing_rejected_job() {
+    let MockProverConfigs {
+        mut plonk_config,
+        dummy_config: _,
+        prover_options,
+        shutdown_request,
+        prover_name,
+    } = MockProverConfigs::default();
+    // The jobs of the mock client are blocks of size 10, which the prover doesn't support.
+    plonk_config.all_block_sizes = vec![32];
+    plonk_config.block_sizes = vec![32];
+
+    let prover = PlonkStepByStepProver::create_from_config(plonk_config, &prover_name);
+    let client = MockApiClient::default();
+
+    let prover_work_cycle = zksync_prover::prover_work_cycle(
+        prover,
+        client.clone(),
+        shutdown_request.clone(),
+        prover_options.clone(),
+        &prover_name,
+    )
+    .fuse();
+    let timeout = tokio::time::sleep(Duration::from_secs(2)).fuse();
+
+    pin_mut!(prover_work_cycle, timeout);
+
+    futures::select! {
+        _ = prover_work_cycle => panic!("prover work ended too quickly"),
+        _ = timeout => {
+            shutdown_request.set();
+            assert_eq!(
+                client.released_jobs.lock().await.get(&0).cloned(),
+                Some("Test".to_string())
             );
+            assert!(client.working_on.lock().await.is_empty());
+            assert!(client.published_prof.lock().await.is_empty());
         },
     };
 }
@@ -262,6 +327,8 @@ struct MockApiClient {
     published_prof: Arc<Mutex<HashMap<i32, ProverOutputRequest>>>,
     /// Received heartbeats from `self.working_on()`.
     working_on: Arc<Mutex<HashMap<i32, String>>>,
+    /// Jobs given back to the queue from `self.release_job()`.
+    released_jobs: Arc<Mutex<HashMap<i32, String>>>,
     /// `gob_id` of the last work that has not yet been submitted.
     last_job_id: Arc<Mutex<i32>>,
 }
@@ -296,7 +363,167 @@ impl zksync_prover::ApiClient for MockApiClient {
         Ok(())
     }
 
+    async fn release_job(&self, job_id: i32, prover_name: &str) -> anyhow::Result<()> {
+        self.released_jobs
+            .lock()
+            .await
+            .insert(job_id, prover_name.to_string());
+
+        Ok(())
+    }
+
     async fn prover_stopped(&self, _: String) -> anyhow::Result<()> {
         Ok(())
     }
 }
+
+#[test]
+fn test_prover_data_used_chunks() {
+    let prover_data = match test_data_for_prover() {
+        JobRequestData::BlockProof(prover_data, _) => prover_data,
+        _ => unreachable!(),
+    };
+    assert_eq!(prover_data.used_chunks(), DepositOp::CHUNKS);
+}
+
+#[test]
+fn test_prover_data_withdraws_to_l1() {
+    let mut prover_data = match test_data_for_prover() {
+        JobRequestData::BlockProof(prover_data, _) => prover_data,
+        _ => unreachable!(),
+    };
+    assert!(!prover_data.withdraws_to_l1());
+
+    // Pretend that the first operation of the block is a withdrawal.
+    prover_data.operations[0].tx_type =
+        Some(Fr::from_str(&WithdrawOp::OP_CODE.to_string()).unwrap());
+    assert!(prover_data.withdraws_to_l1());
+}
+
+#[test]
+fn test_dump_diagnostics() {
+    let diagnostics_dir = std::env::temp_dir().join("zksync_prover_diagnostics_test");
+    std::fs::create_dir_all(&diagnostics_dir).expect("Failed to create diagnostics dir");
+    let prover_data = match test_data_for_prover() {
+        JobRequestData::BlockProof(prover_data, _) => prover_data,
+        _ => unreachable!(),
+    };
+
+    let path =
+        dump_diagnostics(&diagnostics_dir, &prover_data).expect("Failed to dump diagnostics");
+    assert_eq!(path, diagnostics_dir.join("block_1_diagnostics.txt"));
+
+    let report = std::fs::read_to_string(&path).expect("Diagnostics file must be written");
+    for section in &[
+        "[satisfaction]",
+        "[unconstrained]",
+        "[inputs]",
+        "[public data]",
+    ] {
+        assert!(report.contains(section), "Missing section {}", section);
+    }
+    assert!(report.contains("satisfied: true"));
+}
+
+#[test]
+fn test_proof_timeout() {
+    let MockProverConfigs {
+        mut plonk_config, ..
+    } = MockProverConfigs::default();
+    plonk_config.proof_timeout = Some(Duration::from_millis(1));
+    let prover = PlonkStepByStepProver::create_from_config(plonk_config, "Test");
+
+    let err = prover
+        .create_proof(test_data_for_prover())
+        .expect_err("Proving must time out");
+    assert!(err.to_string().contains("proving timed out after 1ms"));
+}
+
+#[test]
+fn test_can_accept() {
+    let MockProverConfigs { plonk_config, .. } = MockProverConfigs::default();
+    let prover = PlonkStepByStepProver::create_from_config(plonk_config, "Test");
+    prover
+        .can_accept(ProofKind::Block, 10)
+        .expect("Supported block size must be accepted");
+    prover
+        .can_accept(ProofKind::Aggregated, 5)
+        .expect("Proofs fitting the aggregated proof must be accepted");
+    prover
+        .can_accept(ProofKind::Aggregated, 19)
+        .expect_err("Too many proofs to aggregate must be rejected");
+
+    let prover_data = match test_data_for_prover() {
+        JobRequestData::BlockProof(prover_data, _) => prover_data,
+        _ => unreachable!(),
+    };
+    let err = prover
+        .can_accept(ProofKind::Block, 11)
+        .expect_err("Unsupported block size must be rejected");
+    let proof_err = prover
+        .create_proof(JobRequestData::BlockProof(prover_data, 11))
+        .expect_err("Unsupported block size must be rejected");
+    assert!(proof_err.to_string().contains(&err.to_string()));
+}
+
+#[test]
+fn test_capabilities() {
+    let MockProverConfigs {
+        mut plonk_config, ..
+    } = MockProverConfigs::default();
+    // There is no verification key for this block size.
+    plonk_config.all_block_sizes.insert(0, 7);
+    let prover = PlonkStepByStepProver::create_from_config(plonk_config, "Test");
+    assert!(prover.capabilities().block_sizes.contains(&10));
+    assert!(!prover.capabilities().block_sizes.contains(&7));
+
+    let prover_data = match test_data_for_prover() {
+        JobRequestData::BlockProof(prover_data, _) => prover_data,
+        _ => unreachable!(),
+    };
+    let err = prover
+        .can_accept(ProofKind::Block, 7)
+        .expect_err("Block size without verification key must be rejected");
+    assert!(err
+        .to_string()
+        .contains("Verification key for block size 7 is missing"));
+    let proof_err = prover
+        .create_proof(JobRequestData::BlockProof(prover_data, 7))
+        .expect_err("Block size without verification key must be rejected");
+    assert!(proof_err.to_string().contains(&err.to_string()));
+
+    // Verification keys are not needed if the proofs are not verified.
+    let MockProverConfigs {
+        mut plonk_config, ..
+    } = MockProverConfigs::default();
+    plonk_config.all_block_sizes.insert(0, 7);
+    plonk_config.self_verify = false;
+    let prover = PlonkStepByStepProver::create_from_config(plonk_config, "Test");
+    assert_eq!(
+        prover.capabilities(),
+        ProverCapabilities {
+            block_sizes: vec![7, 10, 32, 72, 156, 322, 654]
+        }
+    );
+}
+
+#[test]
+fn test_set_verification_key() {
+    let MockProverConfigs {
+        mut plonk_config, ..
+    } = MockProverConfigs::default();
+    // There is no verification key for this block size.
+    plonk_config.all_block_sizes.insert(0, 7);
+    let prover = PlonkStepByStepProver::create_from_config(plonk_config, "Test");
+    prover
+        .can_accept(ProofKind::Block, 7)
+        .expect_err("Block size without verification key must be rejected");
+
+    let vk = PlonkVerificationKey::read_verification_key_for_main_circuit(10)
+        .expect("Failed to read verification key");
+    prover.set_verification_key(7, vk);
+    assert!(prover.capabilities().block_sizes.contains(&7));
+    prover
+        .can_accept(ProofKind::Block, 7)
+        .expect("Block size with the set verification key must be accepted");
+}
@@ -300,7 +300,10 @@ async fn run_server(components: &ComponentsToRun) {
     }
 
     if components.0.contains(&Component::WitnessGenerator) {
-        tasks.push(run_witness_generator(connection_pool.clone()))
+        tasks.push(run_witness_generator(
+            connection_pool.clone(),
+            read_only_connection_pool.clone(),
+        ))
     }
 
     if components.0.contains(&Component::Prometheus) {
@@ -369,11 +372,18 @@ pub fn run_forced_exit(connection_pool: ConnectionPool) -> Vec<JoinHandle<()>> {
     vec![mempool_task, forced_exit_task]
 }
 
-pub fn run_witness_generator(connection_pool: ConnectionPool) -> JoinHandle<()> {
+pub fn run_witness_generator(
+    connection_pool: ConnectionPool,
+    read_only_connection_pool: ConnectionPool,
+) -> JoinHandle<()> {
     vlog::info!("Starting the Prover server actors");
     let prover_api_config = ProverApiConfig::from_env();
     let prover_config = ProverConfig::from_env();
-    let database = zksync_witness_generator::database::Database::new(connection_pool);
+    // Accounts state is loaded from the replica (if configured), everything else uses the main database.
+    let database = zksync_witness_generator::database::Database::new_with_pools(
+        connection_pool,
+        read_only_connection_pool,
+    );
     run_prover_server(database, prover_api_config, prover_config)
 }
 
@@ -25,11 +25,22 @@ const NUMBER_OF_STORED_ACCOUNT_TREE_CACHE: u32 = 400;
 pub struct Database {
     /// Connection to the database.
     db_pool: ConnectionPool,
+    /// Connection used to load the accounts state.
+    read_pool: ConnectionPool,
 }
 
 impl Database {
     pub fn new(db_pool: ConnectionPool) -> Self {
-        Self { db_pool }
+        Self {
+            read_pool: db_pool.clone(),
+            db_pool,
+        }
+    }
+
+    /// Creates the database wrapper loading the accounts state through `read_pool`,
+    /// e.g. connected to a read replica, and using `db_pool` for everything else.
+    pub fn new_with_pools(db_pool: ConnectionPool, read_pool: ConnectionPool) -> Self {
+        Self { db_pool, read_pool }
     }
 }
 
@@ -41,6 +52,12 @@ impl DatabaseInterface for Database {
         Ok(connection)
     }
 
+    async fn acquire_read_connection(&self) -> anyhow::Result<StorageProcessor<'_>> {
+        let connection = self.read_pool.access_storage().await?;
+
+        Ok(connection)
+    }
+
     async fn load_last_block_prover_job_queue(
         &self,
         connection: &mut StorageProcessor<'_>,
@@ -154,10 +171,11 @@ impl DatabaseInterface for Database {
     async fn load_idle_prover_job_from_job_queue(
         &self,
         connection: &mut StorageProcessor<'_>,
+        prover_name: &str,
     ) -> anyhow::Result<Option<ProverJob>> {
         let proof = connection
             .prover_schema()
-            .get_idle_prover_job_from_job_queue()
+            .get_idle_prover_job_from_job_queue(prover_name)
             .await?;
 
         Ok(proof)
@@ -208,6 +226,20 @@ impl DatabaseInterface for Database {
         Ok(())
     }
 
+    async fn release_prover_job(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        job_id: i32,
+        prover_name: &str,
+    ) -> anyhow::Result<()> {
+        connection
+            .prover_schema()
+            .release_prover_job(job_id, prover_name)
+            .await?;
+
+        Ok(())
+    }
+
     async fn record_prover_stop(
         &self,
         connection: &mut StorageProcessor<'_>,
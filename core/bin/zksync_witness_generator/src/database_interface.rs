@@ -22,6 +22,12 @@ pub trait DatabaseInterface: Send + Sync + Clone + 'static {
     /// Returns connection to the database.
     async fn acquire_connection(&self) -> anyhow::Result<StorageProcessor<'_>>;
 
+    /// Returns connection used to load the accounts state, which may point to a read replica
+    /// to take the load off the main database. Uses `acquire_connection` by default.
+    async fn acquire_read_connection(&self) -> anyhow::Result<StorageProcessor<'_>> {
+        self.acquire_connection().await
+    }
+
     /// Returns the block number with the largest last block.
     async fn load_last_block_prover_job_queue(
         &self,
@@ -83,6 +89,7 @@ pub trait DatabaseInterface: Send + Sync + Clone + 'static {
     async fn load_idle_prover_job_from_job_queue(
         &self,
         connection: &mut StorageProcessor<'_>,
+        prover_name: &str,
     ) -> anyhow::Result<Option<ProverJob>>;
 
     async fn record_prover_is_working(
@@ -109,6 +116,13 @@ pub trait DatabaseInterface: Send + Sync + Clone + 'static {
         proof: &AggregatedProof,
     ) -> anyhow::Result<()>;
 
+    async fn release_prover_job(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        job_id: i32,
+        prover_name: &str,
+    ) -> anyhow::Result<()>;
+
     async fn record_prover_stop(
         &self,
         connection: &mut StorageProcessor<'_>,
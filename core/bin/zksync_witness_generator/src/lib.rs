@@ -26,13 +26,14 @@ use zksync_circuit::serialization::ProverData;
 use zksync_config::configs::api::ProverApiConfig;
 use zksync_prover_utils::api::{
     JobRequestData, JobResultData, ProverInputRequest, ProverInputResponse, ProverOutputRequest,
-    WorkingOn,
+    ReleaseJob, WorkingOn,
 };
 use zksync_types::aggregated_operations::{
     AggregatedActionType, AggregatedOperation, BlocksCreateProofOperation,
 };
 use zksync_types::prover::{
     ProverJobType, AGGREGATED_PROOF_JOB_PRIORITY, SINGLE_PROOF_JOB_PRIORITY,
+    WITHDRAWAL_BLOCK_PROOF_JOB_PRIORITY,
 };
 use zksync_types::BlockNumber;
 use zksync_utils::panic_notify::{spawn_panic_handler, ThreadPanicNotify};
@@ -130,7 +131,7 @@ async fn get_job<DB: DatabaseInterface>(
     let mut storage = data.access_storage().await?;
     let ret = data
         .database
-        .load_idle_prover_job_from_job_queue(&mut storage)
+        .load_idle_prover_job_from_job_queue(&mut storage, &r.prover_name)
         .await
         .map_err(|e| {
             vlog::warn!("could not get next unverified commit operation: {}", e);
@@ -190,9 +191,10 @@ async fn publish<DB: DatabaseInterface>(
     let storage_result = match &r.data {
         JobResultData::BlockProof(single_proof) => {
             vlog::info!(
-                "Received a proof for job: {}, single block: {}",
+                "Received a proof for job: {}, single block: {}, proving time: {} ms",
                 r.job_id,
-                r.first_block
+                r.first_block,
+                r.proving_ms
             );
             data.database
                 .store_proof(&mut storage, r.job_id, r.first_block, single_proof)
@@ -200,10 +202,11 @@ async fn publish<DB: DatabaseInterface>(
         }
         JobResultData::AggregatedBlockProof(aggregated_proof) => {
             vlog::info!(
-                "Received a proof for job: {}, aggregated blocks: [{},{}]",
+                "Received a proof for job: {}, aggregated blocks: [{},{}], proving time: {} ms",
                 r.job_id,
                 r.first_block,
-                r.last_block
+                r.last_block,
+                r.proving_ms
             );
             data.database
                 .store_aggregated_proof(
@@ -229,6 +232,30 @@ async fn publish<DB: DatabaseInterface>(
     Ok(HttpResponse::Ok().finish())
 }
 
+async fn release_job<DB: DatabaseInterface>(
+    data: web::Data<AppState<DB>>,
+    r: web::Json<ReleaseJob>,
+) -> actix_web::Result<HttpResponse> {
+    vlog::info!(
+        "Prover instance '{}' gives back the job with id: {}",
+        r.prover_name,
+        r.job_id
+    );
+    let mut storage = data
+        .access_storage()
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    data.database
+        .release_prover_job(&mut storage, r.job_id, &r.prover_name)
+        .await
+        .map_err(|e| {
+            vlog::warn!("failed to release prover job: {}", e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
 async fn stopped<DB: DatabaseInterface>(
     data: web::Data<AppState<DB>>,
     prover_name: web::Json<String>,
@@ -318,6 +345,11 @@ async fn update_prover_job_queue<DB: DatabaseInterface>(database: DB) -> anyhow:
             let prover_data: ProverData =
                 serde_json::from_value(witness).expect("incorrect single block witness");
             let block_size = prover_data.operations.len();
+            let job_priority = if prover_data.withdraws_to_l1() {
+                WITHDRAWAL_BLOCK_PROOF_JOB_PRIORITY
+            } else {
+                SINGLE_PROOF_JOB_PRIORITY
+            };
             let job_data =
                 serde_json::to_value(JobRequestData::BlockProof(prover_data, block_size))
                     .expect("Failed to serialize single proof job data");
@@ -327,7 +359,7 @@ async fn update_prover_job_queue<DB: DatabaseInterface>(database: DB) -> anyhow:
                     next_single_block_to_add,
                     next_single_block_to_add,
                     job_data,
-                    SINGLE_PROOF_JOB_PRIORITY,
+                    job_priority,
                     ProverJobType::SingleProof,
                 )
                 .await?;
@@ -470,6 +502,7 @@ pub fn run_prover_server<DB: DatabaseInterface>(
                         .route("/get_job", web::get().to(get_job::<DB>))
                         .route("/working_on", web::post().to(working_on::<DB>))
                         .route("/publish", web::post().to(publish::<DB>))
+                        .route("/release_job", web::post().to(release_job::<DB>))
                         .route("/stopped", web::post().to(stopped::<DB>))
                         .route(
                             "/api/internal/prover/replicas",
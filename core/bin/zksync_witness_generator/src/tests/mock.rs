@@ -2,6 +2,7 @@
 // Built-in
 use std::clone::Clone;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 // External uses
@@ -32,6 +33,8 @@ pub struct MockDatabase {
     blocks: Arc<RwLock<Vec<Block>>>,
     account_tree_cache: Arc<RwLock<AccountTreeCache>>,
     accounts_state: Arc<RwLock<(u32, AccountMap)>>,
+    /// Number of acquired connections for loading the accounts state.
+    read_connections: Arc<AtomicUsize>,
 }
 
 impl MockDatabase {
@@ -49,6 +52,7 @@ impl MockDatabase {
                 tree_cache_binary,
             })),
             accounts_state: Arc::new(RwLock::new((0, accounts))),
+            read_connections: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -75,6 +79,10 @@ impl MockDatabase {
     pub async fn add_block(&self, block: Block) {
         self.blocks.write().await.push(block);
     }
+
+    pub fn read_connections_count(&self) -> usize {
+        self.read_connections.load(Ordering::SeqCst)
+    }
 }
 
 #[async_trait::async_trait]
@@ -85,6 +93,11 @@ impl DatabaseInterface for MockDatabase {
         StorageProcessor::establish_connection().await
     }
 
+    async fn acquire_read_connection(&self) -> anyhow::Result<StorageProcessor<'_>> {
+        self.read_connections.fetch_add(1, Ordering::SeqCst);
+        StorageProcessor::establish_connection().await
+    }
+
     async fn add_prover_job_to_job_queue(
         &self,
         _: &mut StorageProcessor<'_>,
@@ -224,11 +237,15 @@ impl DatabaseInterface for MockDatabase {
     async fn load_idle_prover_job_from_job_queue(
         &self,
         _: &mut StorageProcessor<'_>,
+        prover_name: &str,
     ) -> anyhow::Result<Option<ProverJob>> {
+        let released_by = format!("server_release_job_{}", prover_name);
         let prover_job_queue = &mut self.prover_job_queue.write().await.1;
         let idle_prover_job = prover_job_queue
             .iter_mut()
-            .filter(|job| job.job_status == ProverJobStatus::Idle.to_number())
+            .filter(|job| {
+                job.job_status == ProverJobStatus::Idle.to_number() && job.updated_by != released_by
+            })
             .max_by_key(|job| (job.job_priority, job.id));
 
         let prover_job = if let Some(job) = idle_prover_job {
@@ -302,6 +319,26 @@ impl DatabaseInterface for MockDatabase {
         unreachable!();
     }
 
+    async fn release_prover_job(
+        &self,
+        _: &mut StorageProcessor<'_>,
+        job_id: i32,
+        prover_name: &str,
+    ) -> anyhow::Result<()> {
+        let prover_job_queue = &mut self.prover_job_queue.write().await.1;
+        let prover_job = prover_job_queue.iter_mut().find(|job| {
+            job.id == job_id && job.job_status == ProverJobStatus::InProgress.to_number()
+        });
+
+        if let Some(job) = prover_job {
+            job.job_status = ProverJobStatus::Idle.to_number();
+            job.updated_at = Utc::now();
+            job.updated_by = format!("server_release_job_{}", prover_name);
+        }
+
+        Ok(())
+    }
+
     async fn record_prover_stop(
         &self,
         _: &mut StorageProcessor<'_>,
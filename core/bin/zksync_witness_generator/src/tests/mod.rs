@@ -1,2 +1,2 @@
-mod mock;
+pub(crate) mod mock;
 mod prover_server;
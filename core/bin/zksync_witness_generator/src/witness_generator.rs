@@ -1,6 +1,7 @@
 use std::time::Instant;
 // Built-in
 use std::collections::BTreeMap;
+use std::future::Future;
 use std::ops::Bound::{Included, Unbounded};
 use std::sync::Arc;
 use std::{thread, time};
@@ -15,10 +16,122 @@ use zksync_circuit::serialization::ProverData;
 use zksync_circuit::witness::utils::build_block_witness;
 use zksync_crypto::circuit::CircuitAccountTree;
 use zksync_crypto::params::account_tree_depth;
+use zksync_crypto::Fr;
+use zksync_storage::StorageProcessor;
 use zksync_types::block::Block;
-use zksync_types::BlockNumber;
+use zksync_types::{AccountId, BlockNumber};
 use zksync_utils::panic_notify::ThreadPanicNotify;
 
+/// Number of attempts to acquire the database connection before giving up.
+const DB_CONNECTION_ATTEMPTS: usize = 5;
+/// Delay before the second attempt to acquire the database connection.
+/// It is doubled after each subsequent failure.
+const DB_CONNECTION_INITIAL_BACKOFF: time::Duration = time::Duration::from_millis(100);
+/// Number of accounts updated by a single state diff after which a warning is reported.
+const LARGE_STATE_DIFF_THRESHOLD: usize = 100_000;
+
+/// Runs `f` until it succeeds, but no more than `attempts` times,
+/// doubling the delay between the attempts. Returns the last error on exhaustion.
+async fn retry_with_backoff<T, F, Fut>(
+    attempts: usize,
+    initial_delay: time::Duration,
+    mut f: F,
+) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut delay = initial_delay;
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= attempts => {
+                return Err(err.context(format!("Failed after {} attempts", attempts)));
+            }
+            Err(err) => {
+                vlog::warn!(
+                    "Attempt {}/{} failed: {}, retrying in {:?}",
+                    attempt,
+                    attempts,
+                    err,
+                    delay
+                );
+                sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Returns the sorted and deduplicated ids of the accounts updated by a state diff.
+///
+/// The account tree can't hold more than `max_accounts` accounts, so a larger diff means
+/// that the loaded state is broken; it's rejected instead of being inserted into the tree.
+fn updated_account_ids(
+    account_updates: impl IntoIterator<Item = AccountId>,
+    max_accounts: usize,
+    warn_threshold: usize,
+) -> anyhow::Result<Vec<AccountId>> {
+    let mut updated_accounts = account_updates.into_iter().collect::<Vec<_>>();
+    updated_accounts.sort_unstable();
+    updated_accounts.dedup();
+
+    anyhow::ensure!(
+        updated_accounts.len() <= max_accounts,
+        "State diff updates {} accounts, while the account tree can hold only {}",
+        updated_accounts.len(),
+        max_accounts
+    );
+    if updated_accounts.len() > warn_threshold {
+        vlog::warn!(
+            "State diff updates {} accounts, which exceeds the threshold of {}",
+            updated_accounts.len(),
+            warn_threshold
+        );
+    }
+    Ok(updated_accounts)
+}
+
+/// Checks the root hash of the account tree restored for `block` against the stored one.
+/// Diverging roots mean that the stored state is inconsistent with the stored blocks.
+fn check_restored_root_hash(block: &Block, restored_root_hash: Fr) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        block.new_root_hash == restored_root_hash,
+        "account tree root hash restored incorrectly for block {}: stored root {}, restored root {}",
+        block.block_number,
+        block.new_root_hash,
+        restored_root_hash
+    );
+    Ok(())
+}
+
+/// Acquires the database connection, retrying with exponential backoff on failures,
+/// so a transient database hiccup doesn't fail the whole witness generation round.
+async fn acquire_connection_with_retry<DB: DatabaseInterface>(
+    database: &DB,
+) -> anyhow::Result<StorageProcessor<'_>> {
+    retry_with_backoff(
+        DB_CONNECTION_ATTEMPTS,
+        DB_CONNECTION_INITIAL_BACKOFF,
+        || database.acquire_connection(),
+    )
+    .await
+}
+
+/// Same as `acquire_connection_with_retry`, but for the connection loading the accounts state.
+async fn acquire_read_connection_with_retry<DB: DatabaseInterface>(
+    database: &DB,
+) -> anyhow::Result<StorageProcessor<'_>> {
+    retry_with_backoff(
+        DB_CONNECTION_ATTEMPTS,
+        DB_CONNECTION_INITIAL_BACKOFF,
+        || database.acquire_read_connection(),
+    )
+    .await
+}
+
 /// The essential part of this structure is `maintain` function
 /// which runs forever and adds data to the database.
 ///
@@ -87,7 +200,7 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
         block_number: BlockNumber,
     ) -> Result<BlockInfo, anyhow::Error> {
         let start = Instant::now();
-        let mut storage = self.database.acquire_connection().await?;
+        let mut storage = acquire_connection_with_retry(&self.database).await?;
         let mut transaction = storage.start_transaction().await?;
         let block = self
             .database
@@ -121,13 +234,14 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
             return Ok(Some((*block, cache.clone())));
         }
         drop(cache);
-        let mut storage = self.database.acquire_connection().await?;
+        let mut storage = acquire_connection_with_retry(&self.database).await?;
         if let Some((block, cache)) = self.database.load_account_tree_cache(&mut storage).await? {
             let mut circuit_account_tree = CircuitAccountTree::new(account_tree_depth());
 
+            let mut read_storage = acquire_read_connection_with_retry(&self.database).await?;
             let (_, accounts) = self
                 .database
-                .load_committed_state(&mut storage, Some(block))
+                .load_committed_state(&mut read_storage, Some(block))
                 .await?;
             for (id, account) in accounts {
                 circuit_account_tree.insert(*id, account.into());
@@ -151,7 +265,8 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
         let start = Instant::now();
         let cache = self.load_account_tree_cache(block).await?;
         metrics::histogram!("witness_generator", start.elapsed(), "stage" => "load_cache");
-        let mut storage = self.database.acquire_connection().await?;
+        let mut storage = acquire_connection_with_retry(&self.database).await?;
+        let mut read_storage = acquire_read_connection_with_retry(&self.database).await?;
         let start = Instant::now();
 
         let circuit_account_tree = if let Some((cached_block, mut circuit_account_tree)) = cache {
@@ -168,19 +283,18 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
 
                 let (_, accounts) = self
                     .database
-                    .load_committed_state(&mut storage, Some(block))
+                    .load_committed_state(&mut read_storage, Some(block))
                     .await?;
                 if let Some((_, account_updates)) = self
                     .database
-                    .load_state_diff(&mut storage, block, Some(cached_block))
+                    .load_state_diff(&mut read_storage, block, Some(cached_block))
                     .await?
                 {
-                    let mut updated_accounts = account_updates
-                        .into_iter()
-                        .map(|(id, _)| id)
-                        .collect::<Vec<_>>();
-                    updated_accounts.sort_unstable();
-                    updated_accounts.dedup();
+                    let updated_accounts = updated_account_ids(
+                        account_updates.into_iter().map(|(id, _)| id),
+                        1 << account_tree_depth(),
+                        LARGE_STATE_DIFF_THRESHOLD,
+                    )?;
                     for idx in updated_accounts {
                         circuit_account_tree
                             .insert(*idx, accounts.get(&idx).cloned().unwrap_or_default().into());
@@ -212,7 +326,7 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
             // There are no caches at all.
             let (_, accounts) = self
                 .database
-                .load_committed_state(&mut storage, Some(block))
+                .load_committed_state(&mut read_storage, Some(block))
                 .await?;
             for (id, account) in accounts {
                 circuit_account_tree.insert(*id, account.into());
@@ -242,11 +356,7 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
                 .load_block(&mut storage, block)
                 .await?
                 .expect("Block for witness generator must exist");
-            assert_eq!(
-                storage_block.new_root_hash,
-                circuit_account_tree.root_hash(),
-                "account tree root hash restored incorrectly"
-            );
+            check_restored_root_hash(&storage_block, circuit_account_tree.root_hash())?;
         }
         self.remove_cache(block).await;
         metrics::histogram!("witness_generator", start.elapsed(), "stage" => "ensure_root_hash");
@@ -278,7 +388,7 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
         metrics::histogram!("witness_generator", start.elapsed(), "stage" => "build_witness");
 
         let start = Instant::now();
-        let mut storage = self.database.acquire_connection().await?;
+        let mut storage = acquire_connection_with_retry(&self.database).await?;
         self.database
             .store_witness(
                 &mut storage,
@@ -362,8 +472,45 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
 mod tests {
     use super::*;
     use crate::database::Database;
+    use crate::tests::mock::MockDatabase;
+    use zksync_crypto::ff::Field;
     use zksync_crypto::Fr;
-    use zksync_types::{AccountId, H256, U256};
+    use zksync_types::{H256, U256};
+
+    #[tokio::test]
+    async fn test_retry_with_backoff() {
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let flaky = || {
+            let call = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move {
+                if call < 2 {
+                    anyhow::bail!("connection refused");
+                }
+                Ok(call)
+            }
+        };
+
+        let result = retry_with_backoff(3, time::Duration::from_millis(1), flaky).await;
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+        calls.store(0, std::sync::atomic::Ordering::SeqCst);
+        let result = retry_with_backoff(2, time::Duration::from_millis(1), flaky).await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_updated_account_ids() {
+        let updates = vec![AccountId(3), AccountId(1), AccountId(3), AccountId(2)];
+        assert_eq!(
+            updated_account_ids(updates.clone(), 3, 2).unwrap(),
+            vec![AccountId(1), AccountId(2), AccountId(3)]
+        );
+
+        let oversized = (0..5).map(AccountId);
+        assert!(updated_account_ids(oversized, 4, 2).is_err());
+    }
 
     #[test]
     fn test_next_witness_block() {
@@ -404,4 +551,89 @@ mod tests {
             BlockNumber(7)
         );
     }
+
+    #[test]
+    fn test_check_restored_root_hash() {
+        let block = Block::new(
+            BlockNumber(1),
+            Fr::one(),
+            AccountId(0),
+            Vec::new(),
+            (0, 0),
+            10,
+            U256::default(),
+            U256::default(),
+            H256::default(),
+            0,
+        );
+        check_restored_root_hash(&block, Fr::one()).unwrap();
+
+        let err = check_restored_root_hash(&block, Fr::zero())
+            .expect_err("Inconsistent root hash must be detected");
+        assert!(err
+            .to_string()
+            .contains("account tree root hash restored incorrectly for block 1"));
+    }
+
+    // Mock database still establishes the storage connection.
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    #[tokio::test]
+    async fn test_load_account_tree_inconsistent_root() {
+        let database = MockDatabase::new();
+        // The stored state contains only the fee account, so the root can't be equal to one.
+        database
+            .add_block(Block::new(
+                BlockNumber(1),
+                Fr::one(),
+                AccountId(0),
+                Vec::new(),
+                (0, 0),
+                10,
+                U256::default(),
+                U256::default(),
+                H256::default(),
+                0,
+            ))
+            .await;
+
+        let mut witness_generator = WitnessGenerator::new(
+            database,
+            time::Duration::from_secs(1),
+            time::Duration::from_secs(0),
+            BlockNumber(1),
+            BlockNumber(1),
+            Default::default(),
+        );
+        let err = witness_generator
+            .load_account_tree(BlockNumber(1))
+            .await
+            .expect_err("Inconsistent root hash must be detected");
+        assert!(err
+            .to_string()
+            .contains("account tree root hash restored incorrectly for block 1"));
+    }
+
+    // Mock database still establishes the storage connection.
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    #[tokio::test]
+    async fn test_state_loads_use_read_connection() {
+        let database = MockDatabase::new();
+        let (tree, _) = MockDatabase::get_default_tree_and_accounts();
+
+        let mut witness_generator = WitnessGenerator::new(
+            database.clone(),
+            time::Duration::from_secs(1),
+            time::Duration::from_secs(0),
+            BlockNumber(1),
+            BlockNumber(1),
+            Default::default(),
+        );
+        let circuit_account_tree = witness_generator
+            .load_account_tree(BlockNumber(0))
+            .await
+            .unwrap();
+
+        assert_eq!(circuit_account_tree.root_hash(), tree.root_hash());
+        assert!(database.read_connections_count() > 0);
+    }
 }
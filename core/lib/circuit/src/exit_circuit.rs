@@ -1,5 +1,4 @@
 // External deps
-use crypto::{digest::Digest, sha2::Sha256};
 use zksync_crypto::franklin_crypto::{
     bellman::{
         pairing::ff::{Field, PrimeField, PrimeFieldRepr},
@@ -10,10 +9,7 @@ use zksync_crypto::franklin_crypto::{
 };
 // Workspace deps
 use zksync_crypto::{
-    circuit::{
-        utils::{append_be_fixed_width, be_bit_vector_into_bytes},
-        CircuitAccountTree,
-    },
+    circuit::{utils::append_be_fixed_width, CircuitAccountTree},
     params::{
         ACCOUNT_ID_BIT_WIDTH, ADDRESS_WIDTH, BALANCE_BIT_WIDTH, FR_BIT_WIDTH_PADDED,
         MIN_NFT_TOKEN_ID, NFT_STORAGE_ACCOUNT_ID, SERIAL_ID_WIDTH, SUBTREE_HASH_WIDTH_PADDED,
@@ -30,7 +26,7 @@ use crate::{
     element::CircuitElement,
     operation::{OperationBranch, OperationBranchWitness},
     utils::boolean_or,
-    witness::utils::{apply_leaf_operation, get_audits},
+    witness::utils::{apply_leaf_operation, get_audits, PublicDataCommitment},
 };
 
 #[derive(Clone)]
@@ -318,12 +314,7 @@ pub fn create_exit_circuit_with_public_input(
         append_be_fixed_width(&mut pubdata_commitment, &bit.unwrap(), 1);
     }
 
-    let mut h = Sha256::new();
-
-    let bytes_to_hash = be_bit_vector_into_bytes(&pubdata_commitment);
-    h.input(&bytes_to_hash);
-    let mut hash_result = [0u8; 32];
-    h.result(&mut hash_result[..]);
+    let mut hash_result = PublicDataCommitment::compute(&pubdata_commitment, &[]);
     hash_result[0] &= 0x1f; // temporary solution, this nullifies top bits to be encoded into field element correctly
 
     let mut repr = Fr::zero().into_repr();
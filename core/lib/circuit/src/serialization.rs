@@ -7,13 +7,14 @@ use zksync_crypto::franklin_crypto::alt_babyjubjub::AltJubjubBn256;
 use zksync_crypto::franklin_crypto::rescue::bn256::Bn256RescueParams;
 use zksync_crypto::serialization::*;
 use zksync_crypto::{Engine, Fr};
+use zksync_types::operations::{ForcedExitOp, FullExitOp, NoopOp, WithdrawNFTOp, WithdrawOp};
 // Local
 use crate::account::AccountWitness;
 use crate::circuit::ZkSyncCircuit;
 use crate::operation::{
     Operation, OperationArguments, OperationBranch, OperationBranchWitness, SignatureData,
 };
-use crate::witness::WitnessBuilder;
+use crate::witness::{utils::fr_from, WitnessBuilder};
 
 /// ProverData is data prover needs to calculate proof of the given block.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -74,6 +75,31 @@ impl From<WitnessBuilder<'_>> for ProverData {
 }
 
 impl ProverData {
+    /// Returns the number of block chunks occupied by operations other than noops.
+    pub fn used_chunks(&self) -> usize {
+        let noop_tx_type = Some(fr_from(NoopOp::OP_CODE));
+        self.operations
+            .iter()
+            .filter(|op| op.tx_type != noop_tx_type)
+            .count()
+    }
+
+    /// Returns `true` if the block contains operations withdrawing funds to Ethereum.
+    pub fn withdraws_to_l1(&self) -> bool {
+        let withdrawal_tx_types = [
+            WithdrawOp::OP_CODE,
+            WithdrawNFTOp::OP_CODE,
+            FullExitOp::OP_CODE,
+            ForcedExitOp::OP_CODE,
+        ]
+        .iter()
+        .map(|op_code| Some(fr_from(op_code)))
+        .collect::<Vec<_>>();
+        self.operations
+            .iter()
+            .any(|op| withdrawal_tx_types.contains(&op.tx_type))
+    }
+
     pub fn into_circuit(self) -> ZkSyncCircuit<'static, Engine> {
         ZkSyncCircuit {
             rescue_params: &zksync_crypto::params::RESCUE_PARAMS as &Bn256RescueParams,
@@ -8,19 +8,32 @@
 
 // External deps
 use num::BigUint;
-use zksync_crypto::franklin_crypto::bellman::pairing::bn256::Bn256;
-use zksync_crypto::params::{MIN_NFT_TOKEN_ID, NFT_STORAGE_ACCOUNT_ID, NFT_TOKEN_ID};
+use zksync_crypto::franklin_crypto::{
+    bellman::{
+        pairing::{
+            bn256::Bn256,
+            ff::{PrimeField, PrimeFieldRepr},
+        },
+        Circuit,
+    },
+    circuit::test::TestConstraintSystem,
+};
+use zksync_crypto::params::{
+    CHUNK_BIT_WIDTH, MIN_NFT_TOKEN_ID, NFT_STORAGE_ACCOUNT_ID, NFT_TOKEN_ID,
+};
 // Workspace deps
 use zksync_state::{
     handler::TxHandler,
     state::{TransferOutcome, ZkSyncState},
 };
 use zksync_types::{
+    block::{Block, ExecutedOperations, ExecutedPriorityOp, ExecutedTx},
     operations::{
-        DepositOp, FullExitOp, MintNFTOp, TransferOp, TransferToNewOp, WithdrawNFTOp, WithdrawOp,
+        DepositOp, ForcedExitOp, FullExitOp, MintNFTOp, TransferOp, TransferToNewOp, WithdrawNFTOp,
+        WithdrawOp,
     },
-    AccountId, Address, BlockNumber, Deposit, FullExit, MintNFT, TokenId, Transfer, Withdraw,
-    WithdrawNFT, H256,
+    AccountId, Address, BlockNumber, Deposit, ForcedExit, FullExit, MintNFT, PriorityOp, TokenId,
+    Transfer, Withdraw, WithdrawNFT, ZkSyncOp, ZkSyncPriorityOp, ZkSyncTx, H256,
 };
 // Local deps
 use crate::{
@@ -30,7 +43,13 @@ use crate::{
             check_circuit, check_circuit_non_panicking, WitnessTestAccount, ZkSyncStateGenerator,
             FEE_ACCOUNT_ID,
         },
-        utils::{SigDataInput, WitnessBuilder},
+        utils::{
+            apply_block, build_assignment, build_block_witness, build_block_witness_with_options,
+            build_commit_data, check_operations_count, fr_from, fr_into_be_bits_256,
+            public_data_commitment, public_data_commitment_preimage, tree_diff,
+            AssignmentCollector, BlockWitnessOptions, CommitmentMismatch, InvalidOperation,
+            PublicDataCommitment, RootMismatch, SigDataInput, WitnessBuilder,
+        },
         DepositWitness, FullExitWitness, MintNFTWitness, TransferToNewWitness, TransferWitness,
         WithdrawNFTWitness, WithdrawWitness, Witness,
     },
@@ -503,3 +522,880 @@ fn corrupted_validator_audit_path() {
         expected_msg
     );
 }
+
+/// Checks that `WitnessBuilder::verify_pubdata` accepts the public data produced by the witness
+/// and detects the public data that doesn't match the operations list.
+#[test]
+fn verify_pubdata_detects_corruption() {
+    let account = WitnessTestAccount::new_empty(AccountId(1));
+    let (_, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&[account.clone()]);
+    let mut witness_accum =
+        WitnessBuilder::new(&mut circuit_account_tree, FEE_ACCOUNT_ID, BlockNumber(1), 0);
+
+    let deposit_op = DepositOp {
+        priority_op: Deposit {
+            from: account.account.address,
+            token: TokenId(0),
+            amount: BigUint::from(10u32),
+            to: account.account.address,
+        },
+        account_id: account.id,
+    };
+    let deposit_witness = DepositWitness::apply_tx(witness_accum.account_tree, &deposit_op);
+    witness_accum.add_operation_with_pubdata(
+        deposit_witness.calculate_operations(()),
+        deposit_witness.get_pubdata(),
+        deposit_witness.get_offset_commitment_data(),
+    );
+
+    let ops = vec![ZkSyncOp::Deposit(Box::new(deposit_op))];
+    witness_accum
+        .verify_pubdata(&ops)
+        .expect("Correct pubdata should pass the check");
+
+    // Turn the deposit opcode (0x01) into the withdraw one (0x03), which has the same length.
+    witness_accum.pubdata[6] = true;
+    witness_accum
+        .verify_pubdata(&ops)
+        .expect_err("Corrupted pubdata should be detected");
+}
+
+/// Checks that `WitnessBuilder::check_pubdata_length` detects truncated public data.
+#[test]
+fn check_pubdata_length_detects_truncation() {
+    const BLOCK_SIZE_CHUNKS: usize = 10;
+
+    let account = WitnessTestAccount::new_empty(AccountId(1));
+    let (_, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&[account.clone()]);
+    let mut witness_accum =
+        WitnessBuilder::new(&mut circuit_account_tree, FEE_ACCOUNT_ID, BlockNumber(1), 0);
+
+    let deposit_op = DepositOp {
+        priority_op: Deposit {
+            from: account.account.address,
+            token: TokenId(0),
+            amount: BigUint::from(10u32),
+            to: account.account.address,
+        },
+        account_id: account.id,
+    };
+    let deposit_witness = DepositWitness::apply_tx(witness_accum.account_tree, &deposit_op);
+    witness_accum.add_operation_with_pubdata(
+        deposit_witness.calculate_operations(()),
+        deposit_witness.get_pubdata(),
+        deposit_witness.get_offset_commitment_data(),
+    );
+    witness_accum.extend_pubdata_with_noops(BLOCK_SIZE_CHUNKS);
+    witness_accum
+        .check_pubdata_length(BLOCK_SIZE_CHUNKS)
+        .expect("Correct pubdata should pass the check");
+
+    let new_len = witness_accum.pubdata.len() - 8;
+    witness_accum.pubdata.truncate(new_len);
+    witness_accum
+        .check_pubdata_length(BLOCK_SIZE_CHUNKS)
+        .expect_err("Truncated pubdata should be detected");
+}
+
+/// Pins the chained SHA256 used for the public data commitment to known vectors.
+#[test]
+fn public_data_commitment_hash() {
+    let initial_bits = vec![false; 512];
+
+    let initial_hash = PublicDataCommitment::compute(&initial_bits, &[]);
+    assert_eq!(
+        hex::encode(initial_hash),
+        "f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b"
+    );
+
+    let chained_hash = PublicDataCommitment::compute(&initial_bits, &[&[1, 2, 3]]);
+    assert_eq!(
+        hex::encode(chained_hash),
+        "90212270ba4dbf759dbe7ad1b3b5cbdc140ca4a5fd4e39ff7a7e95ed5eeb5c2d"
+    );
+}
+
+/// Checks that values are encoded into exactly 256 big-endian bits.
+#[test]
+fn fr_into_be_bits_256_padding() {
+    for &block_number in &[0u32, 1, 7, 0x8000_0000, u32::MAX] {
+        let bits = fr_into_be_bits_256::<Bn256>(fr_from(block_number));
+        assert_eq!(bits.len(), 256);
+        assert!(bits[..224].iter().all(|bit| !bit));
+        let low_bits = bits[224..]
+            .iter()
+            .fold(0u32, |acc, &bit| (acc << 1) | bit as u32);
+        assert_eq!(low_bits, block_number);
+    }
+}
+
+/// Checks that the maximum block number is packed into the commitment preimage.
+#[test]
+fn public_data_commitment_preimage_max_block_number() {
+    let preimage = public_data_commitment_preimage::<Bn256>(
+        &[false; CHUNK_BIT_WIDTH],
+        Some(fr_from(1)),
+        Some(fr_from(2)),
+        Some(fr_from(FEE_ACCOUNT_ID)),
+        Some(fr_from(BlockNumber(u32::MAX))),
+        Some(fr_from(1000)),
+        &[false; 8],
+    );
+    let mut expected_block_number = [0u8; 32];
+    expected_block_number[28..].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert_eq!(preimage[0][..32], expected_block_number);
+}
+
+/// Checks that the commitment preimage starts with the block number and hashes into the commitment.
+#[test]
+fn public_data_commitment_preimage_starts_with_block_number() {
+    let pubdata_bits = vec![true; 2 * CHUNK_BIT_WIDTH];
+    let offset_commitment = vec![false; 16];
+    let args = (
+        Some(fr_from(1)),
+        Some(fr_from(2)),
+        Some(fr_from(FEE_ACCOUNT_ID)),
+        Some(fr_from(BlockNumber(7))),
+        Some(fr_from(1000)),
+    );
+
+    let preimage = public_data_commitment_preimage::<Bn256>(
+        &pubdata_bits,
+        args.0,
+        args.1,
+        args.2,
+        args.3,
+        args.4,
+        &offset_commitment,
+    );
+    let mut expected_block_number = [0u8; 32];
+    expected_block_number[28..].copy_from_slice(&7u32.to_be_bytes());
+    assert_eq!(preimage[0][..32], expected_block_number);
+
+    let rounds: Vec<&[u8]> = preimage[1..].iter().map(Vec::as_slice).collect();
+    let mut hash = PublicDataCommitment::compute_from_bytes(&preimage[0], &rounds);
+    hash[0] &= 0x1f;
+    let commitment = public_data_commitment::<Bn256>(
+        &pubdata_bits,
+        args.0,
+        args.1,
+        args.2,
+        args.3,
+        args.4,
+        &offset_commitment,
+    );
+    let mut commitment_bytes = [0u8; 32];
+    commitment
+        .into_repr()
+        .write_be(&mut commitment_bytes[..])
+        .unwrap();
+    assert_eq!(hash, commitment_bytes);
+}
+
+/// Checks that the root hash and the commitment stored in the block are checked separately,
+/// and that the mismatch is only logged if the corresponding check is disabled.
+#[test]
+fn build_block_witness_root_and_commitment_checks() {
+    let account = WitnessTestAccount::new_empty(AccountId(1));
+    let (_, circuit_account_tree) = ZkSyncStateGenerator::generate(&[account]);
+    let root_hash = circuit_account_tree.root_hash();
+
+    // The commitment is calculated from the wrong root hash, so both checks fail.
+    let block = Block::new_from_available_block_sizes(
+        BlockNumber(1),
+        fr_from(42),
+        FEE_ACCOUNT_ID,
+        Vec::new(),
+        (0, 0),
+        &[10],
+        Default::default(),
+        Default::default(),
+        Block::encode_fr_for_eth(root_hash),
+        0,
+    );
+
+    let err = build_block_witness(&mut circuit_account_tree.clone(), &block)
+        .err()
+        .expect("Root hash mismatch should be detected");
+    let err = err
+        .downcast::<RootMismatch>()
+        .expect("Unexpected error type");
+    assert_eq!(
+        err,
+        RootMismatch {
+            block_number: BlockNumber(1),
+            expected: fr_from(42),
+            actual: root_hash,
+        }
+    );
+
+    let options = BlockWitnessOptions {
+        check_new_root: false,
+        ..Default::default()
+    };
+    let err = build_block_witness_with_options(&mut circuit_account_tree.clone(), &block, options)
+        .err()
+        .expect("Commitment mismatch should be detected");
+    assert!(err.downcast_ref::<CommitmentMismatch>().is_some());
+
+    let options = BlockWitnessOptions {
+        check_new_root: false,
+        check_commitment: false,
+        ..Default::default()
+    };
+    let mut unchecked_account_tree = circuit_account_tree.clone();
+    let witness_accum =
+        build_block_witness_with_options(&mut unchecked_account_tree, &block, options)
+            .expect("Witness should be built without the checks");
+    assert_eq!(witness_accum.root_after_fees, Some(root_hash));
+}
+
+/// Applies a transfer between the accounts to the state and returns the block containing it.
+fn transfer_block(
+    plasma_state: &mut ZkSyncState,
+    from: &WitnessTestAccount,
+    to: &WitnessTestAccount,
+    block_number: BlockNumber,
+) -> Block {
+    let transfer_op = TransferOp {
+        tx: from
+            .zksync_account
+            .sign_transfer(
+                TokenId(0),
+                "",
+                BigUint::from(7u32),
+                BigUint::from(3u32),
+                &to.account.address,
+                None,
+                true,
+                Default::default(),
+            )
+            .0,
+        from: from.id,
+        to: to.id,
+    };
+
+    let previous_root_hash = plasma_state.root_hash();
+    let fee = <ZkSyncState as TxHandler<Transfer>>::apply_op(
+        plasma_state,
+        &TransferOutcome::Transfer(transfer_op.clone()),
+    )
+    .expect("Transfer failed")
+    .0;
+    plasma_state.collect_fee(&[fee.expect("Fee should be collected")], FEE_ACCOUNT_ID);
+
+    let executed_transfer = ExecutedOperations::Tx(Box::new(ExecutedTx {
+        signed_tx: ZkSyncTx::Transfer(Box::new(transfer_op.tx.clone())).into(),
+        success: true,
+        op: Some(ZkSyncOp::from(transfer_op)),
+        fail_reason: None,
+        block_index: Some(0),
+        created_at: chrono::Utc::now(),
+        batch_id: None,
+    }));
+    Block::new_from_available_block_sizes(
+        block_number,
+        plasma_state.root_hash(),
+        FEE_ACCOUNT_ID,
+        vec![executed_transfer],
+        (0, 0),
+        &[10],
+        Default::default(),
+        Default::default(),
+        Block::encode_fr_for_eth(previous_root_hash),
+        0,
+    )
+}
+
+/// Checks that forced exits get their own circuit operations in the block witness,
+/// charging the fee from the initiator and withdrawing the funds of the target.
+#[test]
+fn forced_exit_block() {
+    let mut accounts = vec![
+        WitnessTestAccount::new(AccountId(1), 10),
+        WitnessTestAccount::new(AccountId(2), 100),
+    ];
+    // Only accounts without signing key can be forced to exit.
+    accounts[1].set_empty_pubkey_hash();
+    let (mut plasma_state, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let previous_root_hash = plasma_state.root_hash();
+
+    let forced_exit_op = ForcedExitOp {
+        tx: accounts[0].zksync_account.sign_forced_exit(
+            TokenId(0),
+            BigUint::from(3u32),
+            &accounts[1].account.address,
+            None,
+            true,
+            Default::default(),
+        ),
+        target_account_id: accounts[1].id,
+        withdraw_amount: Some(BigUint::from(100u32).into()),
+    };
+    let fee = <ZkSyncState as TxHandler<ForcedExit>>::apply_op(&mut plasma_state, &forced_exit_op)
+        .expect("ForcedExit failed")
+        .0;
+    plasma_state.collect_fee(&[fee.expect("Fee should be collected")], FEE_ACCOUNT_ID);
+
+    let executed_forced_exit = ExecutedOperations::Tx(Box::new(ExecutedTx {
+        signed_tx: ZkSyncTx::ForcedExit(Box::new(forced_exit_op.tx.clone())).into(),
+        success: true,
+        op: Some(ZkSyncOp::from(forced_exit_op)),
+        fail_reason: None,
+        block_index: Some(0),
+        created_at: chrono::Utc::now(),
+        batch_id: None,
+    }));
+    let block = Block::new_from_available_block_sizes(
+        BlockNumber(1),
+        plasma_state.root_hash(),
+        FEE_ACCOUNT_ID,
+        vec![executed_forced_exit],
+        (0, 0),
+        &[10],
+        Default::default(),
+        Default::default(),
+        Block::encode_fr_for_eth(previous_root_hash),
+        0,
+    );
+
+    let witness_accum = build_block_witness(&mut circuit_account_tree, &block)
+        .expect("Forced exit block should be handled");
+    assert_eq!(witness_accum.root_after_fees, Some(block.new_root_hash));
+    let forced_exit_tx_type = Some(fr_from(ForcedExitOp::OP_CODE));
+    assert_eq!(
+        witness_accum
+            .operations
+            .iter()
+            .filter(|op| op.tx_type == forced_exit_tx_type)
+            .count(),
+        ForcedExitOp::CHUNKS
+    );
+}
+
+/// Checks that `tree_diff` reports exactly the accounts changed differently in the two trees.
+#[test]
+fn tree_diff_reports_divergent_accounts() {
+    let accounts = vec![
+        WitnessTestAccount::new(AccountId(1), 100),
+        WitnessTestAccount::new_empty(AccountId(2)),
+        WitnessTestAccount::new_empty(AccountId(3)),
+    ];
+    let (mut plasma_state, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let (mut other_plasma_state, mut other_account_tree) =
+        ZkSyncStateGenerator::generate(&accounts);
+    assert!(tree_diff(&circuit_account_tree, &other_account_tree).is_empty());
+
+    // Both trees transfer the same amount from the first account, but to different recipients.
+    let block = transfer_block(
+        &mut plasma_state,
+        &accounts[0],
+        &accounts[1],
+        BlockNumber(1),
+    );
+    apply_block(&mut circuit_account_tree, &block).expect("Block should be applied");
+    let other_block = transfer_block(
+        &mut other_plasma_state,
+        &accounts[0],
+        &accounts[2],
+        BlockNumber(1),
+    );
+    apply_block(&mut other_account_tree, &other_block).expect("Block should be applied");
+
+    assert_eq!(
+        tree_diff(&circuit_account_tree, &other_account_tree),
+        vec![AccountId(2), AccountId(3)]
+    );
+}
+
+/// Checks that self-transfers are accepted by default and rejected if
+/// `reject_self_transfers` option is set.
+#[test]
+fn reject_self_transfers_option() {
+    let accounts = vec![WitnessTestAccount::new(AccountId(1), 100)];
+    let (mut plasma_state, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let mut rejecting_account_tree = circuit_account_tree.clone();
+    let block = transfer_block(
+        &mut plasma_state,
+        &accounts[0],
+        &accounts[0],
+        BlockNumber(1),
+    );
+
+    let witness_accum = build_block_witness(&mut circuit_account_tree, &block)
+        .expect("Self-transfer should be allowed by default");
+    assert_eq!(witness_accum.root_after_fees, Some(block.new_root_hash));
+
+    let options = BlockWitnessOptions {
+        reject_self_transfers: true,
+        ..Default::default()
+    };
+    let err = build_block_witness_with_options(&mut rejecting_account_tree, &block, options)
+        .err()
+        .expect("Self-transfer should be rejected");
+    let err = err
+        .downcast::<InvalidOperation>()
+        .expect("Unexpected error type");
+    assert_eq!(err.index, 0);
+}
+
+/// Checks that blocks with too many transactions are rejected if `max_transactions` option is set.
+#[test]
+fn max_transactions_option() {
+    let accounts = vec![
+        WitnessTestAccount::new(AccountId(1), 100),
+        WitnessTestAccount::new_empty(AccountId(2)),
+    ];
+    let (mut plasma_state, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let root_hash = circuit_account_tree.root_hash();
+    let block = transfer_block(
+        &mut plasma_state,
+        &accounts[0],
+        &accounts[1],
+        BlockNumber(1),
+    );
+
+    let options = BlockWitnessOptions {
+        max_transactions: Some(0),
+        ..Default::default()
+    };
+    let err = build_block_witness_with_options(&mut circuit_account_tree, &block, options)
+        .err()
+        .expect("Block with too many transactions should be rejected");
+    assert!(err.to_string().contains("at most 0 are allowed"));
+    // The rejection happens before any transaction is applied.
+    assert_eq!(circuit_account_tree.root_hash(), root_hash);
+
+    let options = BlockWitnessOptions {
+        max_transactions: Some(block.block_transactions.len()),
+        ..Default::default()
+    };
+    build_block_witness_with_options(&mut circuit_account_tree, &block, options)
+        .expect("Block within the limit should be accepted");
+}
+
+/// Checks that several deposits to the same account are accepted by default and rejected if
+/// `forbid_duplicate_deposit_targets` option is set.
+#[test]
+fn forbid_duplicate_deposit_targets_option() {
+    let accounts = vec![WitnessTestAccount::new_empty(AccountId(1))];
+    let (mut plasma_state, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let mut rejecting_account_tree = circuit_account_tree.clone();
+    let previous_root_hash = plasma_state.root_hash();
+
+    let deposits = (0..2)
+        .map(|serial_id| {
+            let deposit = Deposit {
+                from: accounts[0].account.address,
+                token: TokenId(0),
+                amount: BigUint::from(10u32),
+                to: accounts[0].account.address,
+            };
+            <ZkSyncState as TxHandler<Deposit>>::apply_tx(&mut plasma_state, deposit.clone())
+                .expect("Deposit failed");
+            let op = ZkSyncOp::from(DepositOp {
+                priority_op: deposit.clone(),
+                account_id: accounts[0].id,
+            });
+            ExecutedOperations::PriorityOp(Box::new(ExecutedPriorityOp {
+                priority_op: PriorityOp {
+                    serial_id,
+                    data: ZkSyncPriorityOp::Deposit(deposit),
+                    deadline_block: 0,
+                    eth_hash: H256::default(),
+                    eth_block: 0,
+                    eth_block_index: None,
+                },
+                op,
+                block_index: serial_id as u32,
+                created_at: chrono::Utc::now(),
+            }))
+        })
+        .collect();
+    let block = Block::new_from_available_block_sizes(
+        BlockNumber(1),
+        plasma_state.root_hash(),
+        FEE_ACCOUNT_ID,
+        deposits,
+        (0, 2),
+        &[2 * DepositOp::CHUNKS],
+        Default::default(),
+        Default::default(),
+        Block::encode_fr_for_eth(previous_root_hash),
+        0,
+    );
+
+    let witness_accum = build_block_witness(&mut circuit_account_tree, &block)
+        .expect("Duplicate deposit targets should be allowed by default");
+    assert_eq!(witness_accum.root_after_fees, Some(block.new_root_hash));
+
+    let options = BlockWitnessOptions {
+        forbid_duplicate_deposit_targets: true,
+        ..Default::default()
+    };
+    let err = build_block_witness_with_options(&mut rejecting_account_tree, &block, options)
+        .err()
+        .expect("Duplicate deposit target should be rejected");
+    let err = err
+        .downcast::<InvalidOperation>()
+        .expect("Unexpected error type");
+    assert_eq!(err.index, 1);
+}
+
+/// Checks that a circuit operation dropped from the witness is detected.
+#[test]
+fn operations_count_mismatch() {
+    let account = WitnessTestAccount::new_empty(AccountId(1));
+    let (_, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&[account.clone()]);
+    let deposit_op = DepositOp {
+        priority_op: Deposit {
+            from: account.account.address,
+            token: TokenId(0),
+            amount: BigUint::from(10u32),
+            to: account.account.address,
+        },
+        account_id: account.id,
+    };
+    let mut operations =
+        DepositWitness::apply_tx(&mut circuit_account_tree, &deposit_op).calculate_operations(());
+    check_operations_count(DepositOp::CHUNKS, &operations).expect("All operations are present");
+
+    operations.pop();
+    let err = check_operations_count(DepositOp::CHUNKS, &operations)
+        .expect_err("Dropped operation must be detected");
+    assert!(err.to_string().contains("witness count mismatch"));
+}
+
+/// Checks that applying blocks without building the circuit instance
+/// yields the same root hashes as the full witness generation.
+#[test]
+fn apply_block_matches_block_witness() {
+    let accounts = vec![
+        WitnessTestAccount::new(AccountId(1), 100),
+        WitnessTestAccount::new_empty(AccountId(2)),
+    ];
+    let (mut plasma_state, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let mut witness_account_tree = circuit_account_tree.clone();
+
+    for block_number in 1..=2 {
+        let block = transfer_block(
+            &mut plasma_state,
+            &accounts[0],
+            &accounts[1],
+            BlockNumber(block_number),
+        );
+
+        let root = apply_block(&mut circuit_account_tree, &block).expect("Block should be applied");
+        let witness_accum = build_block_witness(&mut witness_account_tree, &block)
+            .expect("Witness should be built");
+        assert_eq!(Some(root), witness_accum.root_after_fees);
+        assert_eq!(root, block.new_root_hash);
+    }
+}
+
+/// Checks that blocks are replayed regardless of their commitment, and that
+/// a block with an unexpected root hash is rejected with an error.
+#[test]
+fn apply_block_errors() {
+    let accounts = vec![
+        WitnessTestAccount::new(AccountId(1), 100),
+        WitnessTestAccount::new_empty(AccountId(2)),
+    ];
+    let (mut plasma_state, circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let mut block = transfer_block(
+        &mut plasma_state,
+        &accounts[0],
+        &accounts[1],
+        BlockNumber(1),
+    );
+
+    block.block_commitment = H256::repeat_byte(0x11);
+    let root = apply_block(&mut circuit_account_tree.clone(), &block)
+        .expect("Block should be applied regardless of the commitment");
+    assert_eq!(root, block.new_root_hash);
+
+    block.new_root_hash = fr_from(42);
+    let err = apply_block(&mut circuit_account_tree.clone(), &block)
+        .expect_err("Root hash mismatch should be detected")
+        .downcast::<RootMismatch>()
+        .expect("Unexpected error type");
+    assert_eq!(err.expected, fr_from(42));
+    assert_eq!(err.actual, root);
+}
+
+/// Checks that the operation histogram of the block counts the noop padding separately.
+#[test]
+fn commit_data_op_histogram() {
+    let accounts = vec![
+        WitnessTestAccount::new(AccountId(1), 100),
+        WitnessTestAccount::new_empty(AccountId(2)),
+    ];
+    let (mut plasma_state, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let block = transfer_block(
+        &mut plasma_state,
+        &accounts[0],
+        &accounts[1],
+        BlockNumber(1),
+    );
+
+    let histogram = build_commit_data(&mut circuit_account_tree, &block)
+        .expect("Commit data should be built")
+        .op_histogram()
+        .expect("Public data should be decoded");
+    assert_eq!(histogram.ops.len(), 1);
+    assert_eq!(histogram.ops[&TransferOp::OP_CODE], 1);
+    assert_eq!(
+        histogram.noops,
+        block.block_chunks_size - TransferOp::CHUNKS
+    );
+}
+
+/// Checks that the collected assignment satisfies the circuit constraints
+/// and agrees with the test constraint system.
+///
+/// The block circuit is synthesized three times (into the test constraint system, the collector
+/// and by `build_assignment`), so the test takes a while, especially without optimizations.
+#[test]
+fn assignment_of_transfer_block() {
+    let accounts = vec![
+        WitnessTestAccount::new(AccountId(1), 100),
+        WitnessTestAccount::new_empty(AccountId(2)),
+    ];
+    let (mut plasma_state, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let mut collector_account_tree = circuit_account_tree.clone();
+    let block = transfer_block(
+        &mut plasma_state,
+        &accounts[0],
+        &accounts[1],
+        BlockNumber(1),
+    );
+
+    let circuit = build_block_witness(&mut circuit_account_tree, &block)
+        .expect("Witness should be built")
+        .into_circuit_instance();
+    let pub_data_commitment = circuit.pub_data_commitment;
+    let mut cs = TestConstraintSystem::<Bn256>::new();
+    circuit.clone().synthesize(&mut cs).unwrap();
+    assert!(cs.is_satisfied());
+
+    let mut collector = AssignmentCollector::default();
+    circuit.synthesize(&mut collector).unwrap();
+    assert_eq!(collector.which_is_unsatisfied(), None);
+    assert_eq!(collector.num_inputs(), cs.num_inputs());
+
+    let assignment =
+        build_assignment(&mut collector_account_tree, &block).expect("Assignment should be built");
+    assert_eq!(assignment, collector.assignment());
+    assert_eq!(Some(assignment[1]), pub_data_commitment);
+}
+
+/// Checks that the commit data matches the inputs of the circuit instance for the same block.
+#[test]
+fn commit_data_matches_circuit_instance() {
+    let account = WitnessTestAccount::new_empty(AccountId(1));
+    let (_, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&[account]);
+    let mut commit_account_tree = circuit_account_tree.clone();
+
+    let root_hash = circuit_account_tree.root_hash();
+    let block = Block::new_from_available_block_sizes(
+        BlockNumber(1),
+        root_hash,
+        FEE_ACCOUNT_ID,
+        Vec::new(),
+        (0, 0),
+        &[10],
+        Default::default(),
+        Default::default(),
+        Block::encode_fr_for_eth(root_hash),
+        0,
+    );
+
+    let commit_data =
+        build_commit_data(&mut commit_account_tree, &block).expect("Commit data should be built");
+    assert_eq!(commit_data.new_root, block.new_root_hash);
+    assert_eq!(commit_data.public_data.len(), 10 * CHUNK_BIT_WIDTH / 8);
+
+    let witness_accum =
+        build_block_witness(&mut circuit_account_tree, &block).expect("Witness should be built");
+    assert_eq!(witness_accum.commit_data(), commit_data);
+    let circuit = witness_accum.into_circuit_instance();
+    assert_eq!(
+        circuit.pub_data_commitment,
+        Some(commit_data.public_data_commitment)
+    );
+}
+
+/// Checks that the commit data is built for a block without the commitment,
+/// and that a root hash mismatch is reported as an error.
+#[test]
+fn commit_data_without_block_commitment() {
+    let accounts = vec![
+        WitnessTestAccount::new(AccountId(1), 100),
+        WitnessTestAccount::new_empty(AccountId(2)),
+    ];
+    let (mut plasma_state, circuit_account_tree) = ZkSyncStateGenerator::generate(&accounts);
+    let committed_block = transfer_block(
+        &mut plasma_state,
+        &accounts[0],
+        &accounts[1],
+        BlockNumber(1),
+    );
+    let expected = build_commit_data(&mut circuit_account_tree.clone(), &committed_block)
+        .expect("Commit data should be built");
+
+    let mut block = committed_block;
+    block.block_commitment = H256::default();
+    let commit_data = build_commit_data(&mut circuit_account_tree.clone(), &block)
+        .expect("Commit data should be built without the block commitment");
+    assert_eq!(commit_data, expected);
+
+    block.new_root_hash = fr_from(42);
+    let err = build_commit_data(&mut circuit_account_tree.clone(), &block)
+        .expect_err("Root hash mismatch should be detected")
+        .downcast::<RootMismatch>()
+        .expect("Unexpected error type");
+    assert_eq!(
+        err,
+        RootMismatch {
+            block_number: BlockNumber(1),
+            expected: fr_from(42),
+            actual: expected.new_root,
+        }
+    );
+}
+
+/// Checks that the witness is built only if the calculated commitment
+/// matches `expected_commitment` option.
+#[test]
+fn expected_commitment_option() {
+    let account = WitnessTestAccount::new_empty(AccountId(1));
+    let (_, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&[account]);
+    let mut mismatch_account_tree = circuit_account_tree.clone();
+
+    let root_hash = circuit_account_tree.root_hash();
+    let block = Block::new_from_available_block_sizes(
+        BlockNumber(1),
+        root_hash,
+        FEE_ACCOUNT_ID,
+        Vec::new(),
+        (0, 0),
+        &[10],
+        Default::default(),
+        Default::default(),
+        Block::encode_fr_for_eth(root_hash),
+        0,
+    );
+    let commitment = build_commit_data(&mut circuit_account_tree.clone(), &block)
+        .expect("Commit data should be built")
+        .public_data_commitment;
+
+    let options = BlockWitnessOptions {
+        expected_commitment: Some(commitment),
+        ..Default::default()
+    };
+    build_block_witness_with_options(&mut circuit_account_tree, &block, options)
+        .expect("Witness should be built for the matching commitment");
+
+    let options = BlockWitnessOptions {
+        expected_commitment: Some(fr_from(42)),
+        ..Default::default()
+    };
+    let err = build_block_witness_with_options(&mut mismatch_account_tree, &block, options)
+        .err()
+        .expect("Commitment mismatch should be detected");
+    let err = err
+        .downcast::<CommitmentMismatch>()
+        .expect("Unexpected error type");
+    assert_eq!(
+        err,
+        CommitmentMismatch {
+            block_number: BlockNumber(1),
+            expected: fr_from(42),
+            calculated: commitment,
+        }
+    );
+}
+
+/// Checks that the block is rejected before being applied if the account tree root
+/// doesn't match `expected_old_root` option.
+#[test]
+fn expected_old_root_option() {
+    let account = WitnessTestAccount::new_empty(AccountId(1));
+    let (_, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&[account]);
+
+    let root_hash = circuit_account_tree.root_hash();
+    let block = Block::new_from_available_block_sizes(
+        BlockNumber(1),
+        root_hash,
+        FEE_ACCOUNT_ID,
+        Vec::new(),
+        (0, 0),
+        &[10],
+        Default::default(),
+        Default::default(),
+        Block::encode_fr_for_eth(root_hash),
+        0,
+    );
+
+    let options = BlockWitnessOptions {
+        expected_old_root: Some(fr_from(42)),
+        ..Default::default()
+    };
+    let err = build_block_witness_with_options(&mut circuit_account_tree, &block, options)
+        .err()
+        .expect("Root mismatch should be detected");
+    let err = err
+        .downcast::<RootMismatch>()
+        .expect("Unexpected error type");
+    assert_eq!(
+        err,
+        RootMismatch {
+            block_number: BlockNumber(1),
+            expected: fr_from(42),
+            actual: root_hash,
+        }
+    );
+    // The block must not be applied to the tree.
+    assert_eq!(circuit_account_tree.root_hash(), root_hash);
+
+    let options = BlockWitnessOptions {
+        expected_old_root: Some(root_hash),
+        ..Default::default()
+    };
+    build_block_witness_with_options(&mut circuit_account_tree, &block, options)
+        .expect("Witness should be built for the matching root");
+}
+
+/// Checks that `WitnessBuilder::verify_pubdata_commitment` detects the public data
+/// corrupted after the commitment calculation.
+#[test]
+fn verify_pubdata_commitment_detects_corruption() {
+    let account = WitnessTestAccount::new_empty(AccountId(1));
+    let (_, mut circuit_account_tree) = ZkSyncStateGenerator::generate(&[account.clone()]);
+    let mut witness_accum =
+        WitnessBuilder::new(&mut circuit_account_tree, FEE_ACCOUNT_ID, BlockNumber(1), 0);
+
+    let deposit_op = DepositOp {
+        priority_op: Deposit {
+            from: account.account.address,
+            token: TokenId(0),
+            amount: BigUint::from(10u32),
+            to: account.account.address,
+        },
+        account_id: account.id,
+    };
+    let deposit_witness = DepositWitness::apply_tx(witness_accum.account_tree, &deposit_op);
+    witness_accum.add_operation_with_pubdata(
+        deposit_witness.calculate_operations(()),
+        deposit_witness.get_pubdata(),
+        deposit_witness.get_offset_commitment_data(),
+    );
+    witness_accum.collect_fees(&[]);
+    witness_accum.calculate_pubdata_commitment();
+    assert!(witness_accum.verify_pubdata_commitment());
+
+    // Flip the last bit of the public data.
+    let last_bit = witness_accum.pubdata.len() - 1;
+    witness_accum.pubdata[last_bit] = !witness_accum.pubdata[last_bit];
+    assert!(!witness_accum.verify_pubdata_commitment());
+}
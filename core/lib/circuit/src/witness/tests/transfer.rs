@@ -9,7 +9,7 @@ use zksync_state::{
 use zksync_types::{
     operations::TransferOp,
     tx::{TimeRange, Transfer, TxSignature},
-    AccountId, Nonce, TokenId,
+    AccountId, Nonce, TokenId, ZkSyncOp,
 };
 // Local deps
 use crate::witness::{
@@ -17,7 +17,7 @@ use crate::witness::{
         corrupted_input_test_scenario, generic_test_scenario, incorrect_op_test_scenario,
         WitnessTestAccount, BLOCK_TIMESTAMP,
     },
-    transfer::TransferWitness,
+    transfer::{TransferData, TransferWitness},
     utils::SigDataInput,
 };
 use zksync_crypto::params::{number_of_processable_tokens, NFT_STORAGE_ACCOUNT_ID, NFT_TOKEN_ID};
@@ -243,6 +243,70 @@ fn test_transfer_to_self() {
     );
 }
 
+/// Check for execution of `Transfer` with zero amount and non-zero fee in circuit.
+/// Such a transfer must increment the sender nonce and deduct the fee,
+/// while leaving the recipient balance unchanged.
+#[test]
+#[ignore]
+fn test_zero_amount_transfer_with_fee() {
+    const INITIAL_BALANCE: u64 = 10;
+    const FEE: u64 = 3;
+
+    // Input data.
+    let accounts = vec![
+        WitnessTestAccount::new(AccountId(1), INITIAL_BALANCE),
+        WitnessTestAccount::new(AccountId(2), INITIAL_BALANCE),
+    ];
+    let (account_from, account_to) = (&accounts[0], &accounts[1]);
+    let transfer_op = TransferOp {
+        tx: account_from
+            .zksync_account
+            .sign_transfer(
+                TokenId(0),
+                "",
+                BigUint::from(0u32),
+                BigUint::from(FEE),
+                &account_to.account.address,
+                None,
+                true,
+                Default::default(),
+            )
+            .0,
+        from: account_from.id,
+        to: account_to.id,
+    };
+
+    // Additional data required for performing the operation.
+    let input = SigDataInput::from_transfer_op(&transfer_op).expect("SigDataInput creation failed");
+
+    generic_test_scenario::<TransferWitness<Bn256>, _>(
+        &accounts,
+        transfer_op,
+        input,
+        |plasma_state, op| {
+            let raw_op = TransferOutcome::Transfer(op.clone());
+            let fee = <ZkSyncState as TxHandler<Transfer>>::apply_op(plasma_state, &raw_op)
+                .expect("Operation failed")
+                .0
+                .unwrap();
+
+            let sender = plasma_state.get_account(op.from).unwrap();
+            assert_eq!(sender.nonce, op.tx.nonce + 1);
+            assert_eq!(
+                sender.get_balance(TokenId(0)),
+                BigUint::from(INITIAL_BALANCE - FEE)
+            );
+            let recipient = plasma_state.get_account(op.to).unwrap();
+            assert_eq!(
+                recipient.get_balance(TokenId(0)),
+                BigUint::from(INITIAL_BALANCE)
+            );
+
+            vec![fee]
+        },
+    );
+}
+
 /// Checks that corrupted signature data leads to unsatisfied constraints in circuit.
 #[test]
 #[ignore]
@@ -698,3 +762,43 @@ fn test_transfer_with_nft_storage_account_id() {
         |_| {},
     );
 }
+
+/// Checks that the transfer data can be obtained from the operation restored from its public data.
+#[test]
+fn transfer_data_from_public_data() {
+    let accounts = vec![
+        WitnessTestAccount::new(AccountId(1), 10),
+        WitnessTestAccount::new_empty(AccountId(2)),
+    ];
+    let (account_from, account_to) = (&accounts[0], &accounts[1]);
+    let transfer_op = TransferOp {
+        tx: account_from
+            .zksync_account
+            .sign_transfer(
+                TokenId(0),
+                "",
+                BigUint::from(7u32),
+                BigUint::from(3u32),
+                &account_to.account.address,
+                None,
+                true,
+                Default::default(),
+            )
+            .0,
+        from: account_from.id,
+        to: account_to.id,
+    };
+
+    let restored_op =
+        match ZkSyncOp::from_public_data(&ZkSyncOp::from(transfer_op).public_data()).unwrap() {
+            ZkSyncOp::Transfer(op) => op,
+            op => panic!("unexpected operation restored: {:?}", op),
+        };
+    let transfer_data = TransferData::from(restored_op.as_ref());
+
+    assert_eq!(transfer_data.amount, 7);
+    assert_eq!(transfer_data.fee, 3);
+    assert_eq!(transfer_data.token, 0);
+    assert_eq!(transfer_data.from_account_address, *account_from.id);
+    assert_eq!(transfer_data.to_account_address, *account_to.id);
+}
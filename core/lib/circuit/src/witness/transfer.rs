@@ -42,6 +42,24 @@ pub struct TransferData {
     pub valid_until: u64,
 }
 
+/// The operation may be decoded from the public data (see `ZkSyncOp::from_public_data`), since
+/// only the signature and the nonce of the transaction are missing there, and they are not part
+/// of this data: the signature is passed to `calculate_operations` separately.
+impl From<&TransferOp> for TransferData {
+    fn from(transfer: &TransferOp) -> Self {
+        let time_range = transfer.tx.time_range.unwrap_or_default();
+        Self {
+            amount: transfer.tx.amount.to_u128().unwrap(),
+            fee: transfer.tx.fee.to_u128().unwrap(),
+            token: *transfer.tx.token,
+            from_account_address: *transfer.from,
+            to_account_address: *transfer.to,
+            valid_from: time_range.valid_from,
+            valid_until: time_range.valid_until,
+        }
+    }
+}
+
 pub struct TransferWitness<E: RescueEngine> {
     pub from_before: OperationBranch<E>,
     pub from_intermediate: OperationBranch<E>,
@@ -61,18 +79,8 @@ impl Witness for TransferWitness<Bn256> {
     type CalculateOpsInput = SigDataInput;
 
     fn apply_tx(tree: &mut CircuitAccountTree, transfer: &TransferOp) -> Self {
-        let time_range = transfer.tx.time_range.unwrap_or_default();
-        let transfer_data = TransferData {
-            amount: transfer.tx.amount.to_u128().unwrap(),
-            fee: transfer.tx.fee.to_u128().unwrap(),
-            token: *transfer.tx.token,
-            from_account_address: *transfer.from,
-            to_account_address: *transfer.to,
-            valid_from: time_range.valid_from,
-            valid_until: time_range.valid_until,
-        };
         // le_bit_vector_into_field_element()
-        Self::apply_data(tree, &transfer_data)
+        Self::apply_data(tree, &TransferData::from(transfer))
     }
 
     fn get_pubdata(&self) -> Vec<bool> {
@@ -41,6 +41,23 @@ pub struct TransferToNewData {
     pub valid_until: u64,
 }
 
+/// Like `TransferData`, can be obtained from the operation decoded from the public data.
+impl From<&TransferToNewOp> for TransferToNewData {
+    fn from(transfer_to_new: &TransferToNewOp) -> Self {
+        let time_range = transfer_to_new.tx.time_range.unwrap_or_default();
+        Self {
+            amount: transfer_to_new.tx.amount.to_string().parse().unwrap(),
+            fee: transfer_to_new.tx.fee.to_string().parse().unwrap(),
+            token: *transfer_to_new.tx.token,
+            from_account_address: *transfer_to_new.from,
+            to_account_address: *transfer_to_new.to,
+            new_address: eth_address_to_fr(&transfer_to_new.tx.to),
+            valid_from: time_range.valid_from,
+            valid_until: time_range.valid_until,
+        }
+    }
+}
+
 pub struct TransferToNewWitness<E: RescueEngine> {
     pub from_before: OperationBranch<E>,
     pub from_intermediate: OperationBranch<E>,
@@ -60,19 +77,8 @@ impl Witness for TransferToNewWitness<Bn256> {
     type CalculateOpsInput = SigDataInput;
 
     fn apply_tx(tree: &mut CircuitAccountTree, transfer_to_new: &TransferToNewOp) -> Self {
-        let time_range = transfer_to_new.tx.time_range.unwrap_or_default();
-        let transfer_data = TransferToNewData {
-            amount: transfer_to_new.tx.amount.to_string().parse().unwrap(),
-            fee: transfer_to_new.tx.fee.to_string().parse().unwrap(),
-            token: *transfer_to_new.tx.token,
-            from_account_address: *transfer_to_new.from,
-            to_account_address: *transfer_to_new.to,
-            new_address: eth_address_to_fr(&transfer_to_new.tx.to),
-            valid_from: time_range.valid_from,
-            valid_until: time_range.valid_until,
-        };
         // le_bit_vector_into_field_element()
-        Self::apply_data(tree, &transfer_data)
+        Self::apply_data(tree, &TransferToNewData::from(transfer_to_new))
     }
 
     fn get_pubdata(&self) -> Vec<bool> {
@@ -1,11 +1,16 @@
 // External deps
 use crypto::{digest::Digest, sha2::Sha256};
 use num::ToPrimitive;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
 use zksync_crypto::franklin_crypto::{
     alt_babyjubjub::AltJubjubBn256,
-    bellman::pairing::{
-        bn256::{Bn256, Fr},
-        ff::{BitIterator, Field, PrimeField, PrimeFieldRepr},
+    bellman::{
+        pairing::{
+            bn256::{Bn256, Fr},
+            ff::{BitIterator, Field, PrimeField, PrimeFieldRepr},
+        },
+        Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable,
     },
     eddsa::{PrivateKey, PublicKey},
     jubjub::{FixedGenerators, JubjubEngine},
@@ -27,8 +32,8 @@ use zksync_state::state::CollectedFee;
 use zksync_types::{
     block::Block,
     operations::{
-        ChangePubKeyOp, CloseOp, ForcedExitOp, MintNFTOp, SwapOp, TransferOp, TransferToNewOp,
-        WithdrawNFTOp, WithdrawOp,
+        ChangePubKeyOp, CloseOp, ForcedExitOp, MintNFTOp, OpHistogram, SwapOp, TransferOp,
+        TransferToNewOp, WithdrawNFTOp, WithdrawOp,
     },
     tx::{Order, PackedPublicKey, TxVersion},
     AccountId, BlockNumber, ZkSyncOp,
@@ -60,6 +65,25 @@ macro_rules! get_bytes {
         }
     };
 }
+/// Data required to commit the block on the Ethereum smart contract.
+///
+/// Unlike the block proof, it's cheap to calculate, so blocks can be committed
+/// while their proofs are still being generated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitData {
+    pub new_root: Fr,
+    pub public_data_commitment: Fr,
+    pub public_data: Vec<u8>,
+}
+
+impl CommitData {
+    /// Returns the number of operations of each type in the block, including the noops
+    /// padding it, which shows how full the block is.
+    pub fn op_histogram(&self) -> Result<OpHistogram, anyhow::Error> {
+        Ok(OpHistogram::from_public_data(&self.public_data)?)
+    }
+}
+
 /// Wrapper around `CircuitAccountTree`
 /// that simplifies witness generation
 /// used for testing
@@ -141,6 +165,56 @@ impl<'a> WitnessBuilder<'a> {
         }
     }
 
+    /// Decodes the accumulated public data back into operations and checks that it
+    /// matches the provided list of block operations, so that encoder bugs are caught
+    /// before the public data gets into the commitment. Noop padding is ignored.
+    pub fn verify_pubdata(&self, ops: &[ZkSyncOp]) -> Result<(), anyhow::Error> {
+        let pubdata = be_bit_vector_into_bytes(&self.pubdata);
+        let decoded_ops = ZkSyncOp::parse_pubdata_blob(&pubdata)?;
+
+        let is_not_noop = |op: &&ZkSyncOp| !matches!(op, ZkSyncOp::Noop(_));
+        let decoded_ops: Vec<_> = decoded_ops.iter().filter(is_not_noop).collect();
+        let expected_ops: Vec<_> = ops.iter().filter(is_not_noop).collect();
+
+        anyhow::ensure!(
+            decoded_ops.len() == expected_ops.len(),
+            "Public data contains {} operations, while {} are expected",
+            decoded_ops.len(),
+            expected_ops.len()
+        );
+        for (idx, (decoded, expected)) in decoded_ops.iter().zip(expected_ops).enumerate() {
+            anyhow::ensure!(
+                std::mem::discriminant(*decoded) == std::mem::discriminant(expected),
+                "Operation type mismatch at position {}: decoded {:?}, expected {:?}",
+                idx,
+                decoded,
+                expected
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that the accumulated public data (and the offset commitment accompanying it)
+    /// has exactly the size expected for a block of `block_size_chunks` chunks.
+    /// Public data of a wrong size would be hashed into a wrong commitment silently.
+    pub fn check_pubdata_length(&self, block_size_chunks: usize) -> Result<(), anyhow::Error> {
+        anyhow::ensure!(
+            self.pubdata.len() == CHUNK_BIT_WIDTH * block_size_chunks,
+            "Public data length mismatch: got {} bits, expected {} bits for a block of {} chunks",
+            self.pubdata.len(),
+            CHUNK_BIT_WIDTH * block_size_chunks,
+            block_size_chunks
+        );
+        anyhow::ensure!(
+            self.offset_commitment.len() == 8 * block_size_chunks,
+            "Offset commitment length mismatch: got {} bits, expected {} bits for a block of {} chunks",
+            self.offset_commitment.len(),
+            8 * block_size_chunks,
+            block_size_chunks
+        );
+        Ok(())
+    }
+
     /// After operations are added, collect fees.
     pub fn collect_fees(&mut self, fees: &[CollectedFee]) {
         self.root_before_fees = Some(self.account_tree.root_hash());
@@ -207,7 +281,21 @@ impl<'a> WitnessBuilder<'a> {
             crate::witness::utils::get_audits(self.account_tree, *self.fee_account_id, 0);
         self.fee_account_audit_path = Some(fee_account_audit_path);
 
-        let public_data_commitment = crate::witness::utils::public_data_commitment::<Engine>(
+        self.pubdata_commitment = Some(self.compute_pubdata_commitment());
+    }
+
+    /// Recomputes the public data commitment from the accumulated public data and checks
+    /// that it matches the calculated one, so that the public data corrupted after
+    /// the commitment calculation is detected.
+    pub fn verify_pubdata_commitment(&self) -> bool {
+        match self.pubdata_commitment {
+            Some(pubdata_commitment) => self.compute_pubdata_commitment() == pubdata_commitment,
+            None => false,
+        }
+    }
+
+    fn compute_pubdata_commitment(&self) -> Fr {
+        crate::witness::utils::public_data_commitment::<Engine>(
             &self.pubdata,
             Some(self.initial_root_hash),
             Some(
@@ -218,8 +306,21 @@ impl<'a> WitnessBuilder<'a> {
             Some(fr_from(self.block_number)),
             Some(fr_from(self.timestamp)),
             &self.offset_commitment,
-        );
-        self.pubdata_commitment = Some(public_data_commitment);
+        )
+    }
+
+    /// Returns the data required to commit the block.
+    /// Must be called after the public data commitment is calculated.
+    pub fn commit_data(&self) -> CommitData {
+        CommitData {
+            new_root: self
+                .root_after_fees
+                .expect("root after fee should be present at this step"),
+            public_data_commitment: self
+                .pubdata_commitment
+                .expect("pubdata commitment not present"),
+            public_data: be_bit_vector_into_bytes(&self.pubdata),
+        }
     }
 
     /// Finaly, creates circuit instance for given operations.
@@ -307,6 +408,34 @@ pub fn generate_sig_witness(bits: &[bool]) -> (Fr, Fr, Fr) {
     (first_sig_part, second_sig_part, third_sig_part)
 }
 
+/// Chained SHA256 hash used to calculate the block public data commitment.
+///
+/// The initial bits are hashed first, and then every next round hashes the result
+/// of the previous one concatenated with the next piece of data.
+pub struct PublicDataCommitment;
+
+impl PublicDataCommitment {
+    pub fn compute(initial_bits: &[bool], rounds: &[&[u8]]) -> [u8; 32] {
+        Self::compute_from_bytes(&be_bit_vector_into_bytes(initial_bits), rounds)
+    }
+
+    pub fn compute_from_bytes(initial_bytes: &[u8], rounds: &[&[u8]]) -> [u8; 32] {
+        let mut hash_result = Self::sha256(initial_bytes);
+        for &data in rounds {
+            hash_result = Self::sha256(&[&hash_result[..], data].concat());
+        }
+        hash_result
+    }
+
+    fn sha256(bytes: &[u8]) -> [u8; 32] {
+        let mut h = Sha256::new();
+        h.input(bytes);
+        let mut hash_result = [0u8; 32];
+        h.result(&mut hash_result[..]);
+        hash_result
+    }
+}
+
 pub fn public_data_commitment<E: JubjubEngine>(
     pubdata_bits: &[bool],
     initial_root: Option<E::Fr>,
@@ -316,97 +445,79 @@ pub fn public_data_commitment<E: JubjubEngine>(
     timestamp: Option<E::Fr>,
     offset_commitment: &[bool],
 ) -> E::Fr {
-    let mut public_data_initial_bits = vec![];
-
-    // these two are BE encodings because an iterator is BE. This is also an Ethereum standard behavior
-
-    let block_number_bits: Vec<bool> =
-        BitIterator::new(block_number.unwrap().into_repr()).collect();
-
-    public_data_initial_bits.extend(vec![false; 256 - block_number_bits.len()]);
-    public_data_initial_bits.extend(block_number_bits);
-
-    let validator_id_bits: Vec<bool> =
-        BitIterator::new(validator_address.unwrap().into_repr()).collect();
-
-    public_data_initial_bits.extend(vec![false; 256 - validator_id_bits.len()]);
-    public_data_initial_bits.extend(validator_id_bits);
-
-    assert_eq!(public_data_initial_bits.len(), 512);
-
-    let mut h = Sha256::new();
-
-    let bytes_to_hash = be_bit_vector_into_bytes(&public_data_initial_bits);
-
-    h.input(&bytes_to_hash);
-
-    let mut hash_result = [0u8; 32];
-    h.result(&mut hash_result[..]);
-
-    let old_root_bits: Vec<bool> = BitIterator::new(initial_root.unwrap().into_repr()).collect();
-    let mut packed_old_root_bits = vec![false; 256 - old_root_bits.len()];
-    packed_old_root_bits.extend(old_root_bits);
-
-    let packed_old_root_bytes = be_bit_vector_into_bytes(&packed_old_root_bits);
+    let preimage = public_data_commitment_preimage::<E>(
+        pubdata_bits,
+        initial_root,
+        new_root,
+        validator_address,
+        block_number,
+        timestamp,
+        offset_commitment,
+    );
+    let rounds: Vec<&[u8]> = preimage[1..].iter().map(Vec::as_slice).collect();
+    let mut hash_result = PublicDataCommitment::compute_from_bytes(&preimage[0], &rounds);
 
-    let mut packed_with_old_root = vec![];
-    packed_with_old_root.extend(hash_result.iter());
-    packed_with_old_root.extend(packed_old_root_bytes);
+    hash_result[0] &= 0x1f; // temporary solution, this nullifies top bits to be encoded into field element correctly
 
-    h = Sha256::new();
-    h.input(&packed_with_old_root);
-    hash_result = [0u8; 32];
-    h.result(&mut hash_result[..]);
+    let mut repr = E::Fr::zero().into_repr();
+    repr.read_be(&hash_result[..])
+        .expect("pack hash as field element");
 
-    let new_root_bits: Vec<bool> = BitIterator::new(new_root.unwrap().into_repr()).collect();
-    let mut packed_new_root_bits = vec![false; 256 - new_root_bits.len()];
-    packed_new_root_bits.extend(new_root_bits);
+    E::Fr::from_repr(repr).unwrap()
+}
 
-    let packed_new_root_bytes = be_bit_vector_into_bytes(&packed_new_root_bits);
+/// Returns the big-endian bits of the field element padded to 256 bits,
+/// the encoding of the values in the public data commitment.
+pub fn fr_into_be_bits_256<E: JubjubEngine>(value: E::Fr) -> Vec<bool> {
+    let bits: Vec<bool> = BitIterator::new(value.into_repr()).collect();
+    // Values like the block number must never overflow their 256-bit slot
+    // if their types get wider.
+    assert!(bits.len() <= 256, "Value doesn't fit into 256 bits");
+
+    let mut padded_bits = vec![false; 256 - bits.len()];
+    padded_bits.extend(bits);
+    padded_bits
+}
 
-    let mut packed_with_new_root = vec![];
-    packed_with_new_root.extend(hash_result.iter());
-    packed_with_new_root.extend(packed_new_root_bytes);
+/// Returns the data hashed by the rounds of the block public data commitment (see `PublicDataCommitment`),
+/// so that the commitment can be reconstructed and checked by external tools.
+///
+/// The first element is the initial data: 32-byte block number followed by 32-byte validator id.
+/// It's followed by the old root hash, the new root hash, the timestamp and the public data
+/// along with the offset commitment.
+pub fn public_data_commitment_preimage<E: JubjubEngine>(
+    pubdata_bits: &[bool],
+    initial_root: Option<E::Fr>,
+    new_root: Option<E::Fr>,
+    validator_address: Option<E::Fr>,
+    block_number: Option<E::Fr>,
+    timestamp: Option<E::Fr>,
+    offset_commitment: &[bool],
+) -> Vec<Vec<u8>> {
+    let mut public_data_initial_bits = vec![];
 
-    h = Sha256::new();
-    h.input(&packed_with_new_root);
-    hash_result = [0u8; 32];
-    h.result(&mut hash_result[..]);
+    // these two are BE encodings because an iterator is BE. This is also an Ethereum standard behavior
+    public_data_initial_bits.extend(fr_into_be_bits_256::<E>(block_number.unwrap()));
+    public_data_initial_bits.extend(fr_into_be_bits_256::<E>(validator_address.unwrap()));
 
-    let mut timestamp_bits = vec![];
-    let timstamp_unpadded_bits: Vec<bool> =
-        BitIterator::new(timestamp.unwrap().into_repr()).collect();
-    timestamp_bits.extend(vec![false; 256 - timstamp_unpadded_bits.len()]);
-    timestamp_bits.extend(timstamp_unpadded_bits);
-    let timestamp_bytes = be_bit_vector_into_bytes(&timestamp_bits);
-    let mut packed_with_timestamp = vec![];
-    packed_with_timestamp.extend(hash_result.iter());
-    packed_with_timestamp.extend(timestamp_bytes.iter());
+    assert_eq!(public_data_initial_bits.len(), 512);
 
-    h = Sha256::new();
-    h.input(&packed_with_timestamp);
-    hash_result = [0u8; 32];
-    h.result(&mut hash_result[..]);
+    let packed_old_root_bytes =
+        be_bit_vector_into_bytes(&fr_into_be_bits_256::<E>(initial_root.unwrap()));
+    let packed_new_root_bytes =
+        be_bit_vector_into_bytes(&fr_into_be_bits_256::<E>(new_root.unwrap()));
+    let timestamp_bytes = be_bit_vector_into_bytes(&fr_into_be_bits_256::<E>(timestamp.unwrap()));
 
-    let mut final_bytes = vec![];
     let pubdata_with_offset = [pubdata_bits, offset_commitment].concat();
     let pubdata_bytes = be_bit_vector_into_bytes(&pubdata_with_offset);
-    // let pubdata_bytes = be_bit_vector_into_bytes(&pubdata_bits.to_vec());
-    final_bytes.extend(hash_result.iter());
-    final_bytes.extend(pubdata_bytes);
-
-    h = Sha256::new();
-    h.input(&final_bytes);
-    hash_result = [0u8; 32];
-    h.result(&mut hash_result[..]);
-
-    hash_result[0] &= 0x1f; // temporary solution, this nullifies top bits to be encoded into field element correctly
 
-    let mut repr = E::Fr::zero().into_repr();
-    repr.read_be(&hash_result[..])
-        .expect("pack hash as field element");
-
-    E::Fr::from_repr(repr).unwrap()
+    vec![
+        be_bit_vector_into_bytes(&public_data_initial_bits),
+        packed_old_root_bytes,
+        packed_new_root_bytes,
+        timestamp_bytes,
+        pubdata_bytes,
+    ]
 }
 
 pub fn get_audits(
@@ -736,15 +847,331 @@ pub fn get_used_subtree_root_hash(account_tree: &CircuitAccountTree) -> Fr {
     current_hash
 }
 
+/// Returns the ids of the accounts whose leaves differ between the two account trees,
+/// in ascending order. Absent accounts are treated as empty ones.
+///
+/// Useful to find out where the trees of two instances (e.g. the primary and the shadow ones)
+/// have diverged, once their root hashes don't match.
+pub fn tree_diff(tree: &CircuitAccountTree, other: &CircuitAccountTree) -> Vec<AccountId> {
+    let leaf_hash = |tree: &CircuitAccountTree, index: u64| {
+        let account = tree.items.get(&index).cloned().unwrap_or_default();
+        tree.hasher.hash_bits(account.get_bits_le())
+    };
+
+    tree.items
+        .keys()
+        .chain(other.items.keys())
+        .copied()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|&index| leaf_hash(tree, index) != leaf_hash(other, index))
+        .map(|index| AccountId(index as u32))
+        .collect()
+}
+
+/// Error returned when an operation of the block can't be converted into the circuit input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidOperation {
+    /// Position of the transaction in the block.
+    pub index: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for InvalidOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid operation at position {}: {}",
+            self.index, self.reason
+        )
+    }
+}
+
+impl std::error::Error for InvalidOperation {}
+
+/// Error returned when the public data commitment calculated for the block doesn't match
+/// the expected one (see `BlockWitnessOptions::expected_commitment`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitmentMismatch {
+    pub block_number: BlockNumber,
+    pub expected: Fr,
+    pub calculated: Fr,
+}
+
+impl fmt::Display for CommitmentMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Public data commitment mismatch for block {}: expected {}, calculated {}",
+            self.block_number, self.expected, self.calculated
+        )
+    }
+}
+
+impl std::error::Error for CommitmentMismatch {}
+
+/// Error returned when the root hash of the account tree doesn't match the expected one: either
+/// before applying the block (see `BlockWitnessOptions::expected_old_root`), or after applying
+/// it (see `BlockWitnessOptions::check_new_root`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootMismatch {
+    pub block_number: BlockNumber,
+    pub expected: Fr,
+    pub actual: Fr,
+}
+
+impl fmt::Display for RootMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Account tree root hash mismatch for block {}: expected {}, account tree has {}",
+            self.block_number, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for RootMismatch {}
+
+/// Options for the block witness generation.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockWitnessOptions {
+    /// If set, the generated public data is decoded back and checked against
+    /// the block operations (see `WitnessBuilder::verify_pubdata`).
+    pub verify_pubdata: bool,
+    /// If set, the root hash calculated by the witness builder must match the one stored in
+    /// the block (`RootMismatch` is returned otherwise). If not set, the mismatch is only logged,
+    /// which is useful when replaying historical blocks against a reconstructed tree.
+    pub check_new_root: bool,
+    /// If set, the public data commitment calculated by the witness builder must match the block
+    /// commitment (`CommitmentMismatch` is returned otherwise). If not set, the mismatch is only
+    /// logged, e.g. if the block commitment is not calculated yet.
+    pub check_commitment: bool,
+    /// If set, transfers from an account to itself (which only pay the fee) are considered invalid.
+    pub reject_self_transfers: bool,
+    /// If set, blocks with several deposits to the same account are considered invalid,
+    /// e.g. for circuit configurations expecting unique deposit targets.
+    pub forbid_duplicate_deposit_targets: bool,
+    /// If set, the calculated public data commitment must be equal to this value,
+    /// e.g. the one obtained from a reference implementation.
+    pub expected_commitment: Option<Fr>,
+    /// If set, the root hash of the account tree before applying the block must be equal
+    /// to this value, e.g. the one stored in the smart contract. Otherwise the proof would
+    /// be verified locally, but rejected on-chain.
+    pub expected_old_root: Option<Fr>,
+    /// If set, blocks with more transactions are rejected before any of them is applied.
+    /// It's a defensive bound independent of the block size checks.
+    pub max_transactions: Option<usize>,
+}
+
+impl Default for BlockWitnessOptions {
+    fn default() -> Self {
+        Self {
+            verify_pubdata: false,
+            check_new_root: true,
+            check_commitment: true,
+            reject_self_transfers: false,
+            forbid_duplicate_deposit_targets: false,
+            expected_commitment: None,
+            expected_old_root: None,
+            max_transactions: None,
+        }
+    }
+}
+
 pub fn build_block_witness<'a>(
     account_tree: &'a mut CircuitAccountTree,
     block: &Block,
+) -> Result<WitnessBuilder<'a>, anyhow::Error> {
+    build_block_witness_with_options(account_tree, block, BlockWitnessOptions::default())
+}
+
+/// Applies the block to the account tree and returns the data required to commit it,
+/// without creating the circuit instance.
+///
+/// The block commitment is what is being calculated here, so `block.block_commitment` isn't
+/// checked and may be unset. If the resulting account tree root doesn't match
+/// `block.new_root_hash`, `RootMismatch` error is returned.
+pub fn build_commit_data(
+    account_tree: &mut CircuitAccountTree,
+    block: &Block,
+) -> Result<CommitData, anyhow::Error> {
+    let options = BlockWitnessOptions {
+        check_commitment: false,
+        ..Default::default()
+    };
+    Ok(build_block_witness_with_options(account_tree, block, options)?.commit_data())
+}
+
+/// Applies the block to the account tree without creating the circuit instance and
+/// returns the new root hash. Useful to restore the tree state by replaying blocks.
+///
+/// Like `build_commit_data`, doesn't check the block commitment. Invalid operations and
+/// a root hash mismatch are returned as errors, in which case the account tree is left
+/// partially updated and should be discarded.
+pub fn apply_block(
+    account_tree: &mut CircuitAccountTree,
+    block: &Block,
+) -> Result<Fr, anyhow::Error> {
+    Ok(build_commit_data(account_tree, block)?.new_root)
+}
+
+/// Constraint system recording the values of all the circuit variables instead of building
+/// the proving key or the proof. The constraints are checked against the recorded values
+/// as they are enforced.
+pub struct AssignmentCollector {
+    /// Values of the public inputs, starting with the constant one.
+    inputs: Vec<Fr>,
+    /// Values of the auxiliary (private) variables.
+    aux: Vec<Fr>,
+    /// Annotation of the first constraint not satisfied by the assignment.
+    unsatisfied: Option<String>,
+}
+
+impl Default for AssignmentCollector {
+    fn default() -> Self {
+        Self {
+            inputs: vec![Fr::one()],
+            aux: Vec::new(),
+            unsatisfied: None,
+        }
+    }
+}
+
+impl AssignmentCollector {
+    /// Returns the full assignment: the constant one, the public inputs and then
+    /// the auxiliary variables, in the order of their allocation.
+    pub fn assignment(&self) -> Vec<Fr> {
+        self.inputs.iter().chain(&self.aux).cloned().collect()
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Returns the annotation of the first unsatisfied constraint, if any.
+    pub fn which_is_unsatisfied(&self) -> Option<&str> {
+        self.unsatisfied.as_deref()
+    }
+
+    fn eval(&self, lc: &LinearCombination<Engine>) -> Fr {
+        let mut result = Fr::zero();
+        for (var, coeff) in lc.as_ref() {
+            let mut value = match var.get_unchecked() {
+                Index::Input(index) => self.inputs[index],
+                Index::Aux(index) => self.aux[index],
+            };
+            value.mul_assign(coeff);
+            result.add_assign(&value);
+        }
+        result
+    }
+}
+
+impl ConstraintSystem<Engine> for AssignmentCollector {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.aux.push(f()?);
+        Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inputs.push(f()?);
+        Ok(Variable::new_unchecked(Index::Input(self.inputs.len() - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Engine>) -> LinearCombination<Engine>,
+        LB: FnOnce(LinearCombination<Engine>) -> LinearCombination<Engine>,
+        LC: FnOnce(LinearCombination<Engine>) -> LinearCombination<Engine>,
+    {
+        let mut ab = self.eval(&a(LinearCombination::zero()));
+        ab.mul_assign(&self.eval(&b(LinearCombination::zero())));
+        let c = self.eval(&c(LinearCombination::zero()));
+        if ab != c && self.unsatisfied.is_none() {
+            self.unsatisfied = Some(annotation().into());
+        }
+    }
+
+    fn push_namespace<NR, N>(&mut self, _name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+/// Applies the block to the account tree and returns the assignment of the circuit variables
+/// (see `AssignmentCollector::assignment`), e.g. for the external proving backends.
+pub fn build_assignment(
+    account_tree: &mut CircuitAccountTree,
+    block: &Block,
+) -> Result<Vec<Fr>, anyhow::Error> {
+    let circuit = build_block_witness(account_tree, block)?.into_circuit_instance();
+    let mut collector = AssignmentCollector::default();
+    circuit.synthesize(&mut collector)?;
+    if let Some(constraint) = collector.which_is_unsatisfied() {
+        anyhow::bail!(
+            "Assignment doesn't satisfy the constraint {}, block: {}",
+            constraint,
+            block.block_number
+        );
+    }
+    Ok(collector.assignment())
+}
+
+/// Same as `build_block_witness`, but with configurable checks (see `BlockWitnessOptions`).
+pub fn build_block_witness_with_options<'a>(
+    account_tree: &'a mut CircuitAccountTree,
+    block: &Block,
+    options: BlockWitnessOptions,
 ) -> Result<WitnessBuilder<'a>, anyhow::Error> {
     let block_number = block.block_number;
     let block_size = block.block_chunks_size;
 
     vlog::info!("building prover data for block {}", &block_number);
 
+    if let Some(max_transactions) = options.max_transactions {
+        anyhow::ensure!(
+            block.block_transactions.len() <= max_transactions,
+            "Block {} has {} transactions, while at most {} are allowed",
+            block_number,
+            block.block_transactions.len(),
+            max_transactions
+        );
+    }
+
+    if let Some(expected) = options.expected_old_root {
+        let actual = account_tree.root_hash();
+        if actual != expected {
+            return Err(RootMismatch {
+                block_number,
+                expected,
+                actual,
+            }
+            .into());
+        }
+    }
+
     let mut witness_accum = WitnessBuilder::new(
         account_tree,
         block.fee_account,
@@ -755,15 +1182,34 @@ pub fn build_block_witness<'a>(
     let ops = block
         .block_transactions
         .iter()
-        .filter_map(|tx| tx.get_executed_op().cloned());
+        .enumerate()
+        .filter_map(|(index, tx)| tx.get_executed_op().cloned().map(|op| (index, op)));
 
     let mut operations = vec![];
     let mut pub_data = vec![];
     let mut offset_commitment = vec![];
     let mut fees = vec![];
-    for op in ops {
+    let mut deposit_targets = HashSet::new();
+    let mut expected_operations = 0;
+    for (index, op) in ops {
+        let invalid_op = |err: anyhow::Error| InvalidOperation {
+            index,
+            reason: err.to_string(),
+        };
+        if !matches!(op, ZkSyncOp::Noop(_)) {
+            expected_operations += op.chunks();
+        }
         match op {
             ZkSyncOp::Deposit(deposit) => {
+                if options.forbid_duplicate_deposit_targets
+                    && !deposit_targets.insert(deposit.account_id)
+                {
+                    return Err(invalid_op(anyhow::format_err!(
+                        "Duplicate deposit to account {}",
+                        deposit.account_id
+                    ))
+                    .into());
+                }
                 let deposit_witness =
                     DepositWitness::apply_tx(witness_accum.account_tree, &deposit);
 
@@ -773,10 +1219,17 @@ pub fn build_block_witness<'a>(
                 offset_commitment.extend(deposit_witness.get_offset_commitment_data())
             }
             ZkSyncOp::Transfer(transfer) => {
+                if options.reject_self_transfers && transfer.from == transfer.to {
+                    return Err(invalid_op(anyhow::format_err!(
+                        "Transfer from account {} to itself",
+                        transfer.from
+                    ))
+                    .into());
+                }
                 let transfer_witness =
                     TransferWitness::apply_tx(witness_accum.account_tree, &transfer);
 
-                let input = SigDataInput::from_transfer_op(&transfer)?;
+                let input = SigDataInput::from_transfer_op(&transfer).map_err(invalid_op)?;
                 let transfer_operations = transfer_witness.calculate_operations(input);
 
                 operations.extend(transfer_operations);
@@ -791,7 +1244,8 @@ pub fn build_block_witness<'a>(
                 let transfer_to_new_witness =
                     TransferToNewWitness::apply_tx(witness_accum.account_tree, &transfer_to_new);
 
-                let input = SigDataInput::from_transfer_to_new_op(&transfer_to_new)?;
+                let input =
+                    SigDataInput::from_transfer_to_new_op(&transfer_to_new).map_err(invalid_op)?;
                 let transfer_to_new_operations =
                     transfer_to_new_witness.calculate_operations(input);
 
@@ -807,7 +1261,7 @@ pub fn build_block_witness<'a>(
                 let withdraw_witness =
                     WithdrawWitness::apply_tx(witness_accum.account_tree, &withdraw);
 
-                let input = SigDataInput::from_withdraw_op(&withdraw)?;
+                let input = SigDataInput::from_withdraw_op(&withdraw).map_err(invalid_op)?;
                 let withdraw_operations = withdraw_witness.calculate_operations(input);
 
                 operations.extend(withdraw_operations);
@@ -822,7 +1276,7 @@ pub fn build_block_witness<'a>(
                 let close_account_witness =
                     CloseAccountWitness::apply_tx(witness_accum.account_tree, &close);
 
-                let input = SigDataInput::from_close_op(&close)?;
+                let input = SigDataInput::from_close_op(&close).map_err(invalid_op)?;
                 let close_account_operations = close_account_witness.calculate_operations(input);
 
                 operations.extend(close_account_operations);
@@ -849,7 +1303,8 @@ pub fn build_block_witness<'a>(
                     &change_pkhash_op,
                 );
 
-                let input = SigDataInput::from_change_pubkey_op(&change_pkhash_op)?;
+                let input =
+                    SigDataInput::from_change_pubkey_op(&change_pkhash_op).map_err(invalid_op)?;
                 let change_pkhash_operations = change_pkhash_witness.calculate_operations(input);
 
                 operations.extend(change_pkhash_operations);
@@ -864,7 +1319,7 @@ pub fn build_block_witness<'a>(
                 let forced_exit_witness =
                     ForcedExitWitness::apply_tx(witness_accum.account_tree, &forced_exit);
 
-                let input = SigDataInput::from_forced_exit_op(&forced_exit)?;
+                let input = SigDataInput::from_forced_exit_op(&forced_exit).map_err(invalid_op)?;
                 let forced_exit_operations = forced_exit_witness.calculate_operations(input);
 
                 operations.extend(forced_exit_operations);
@@ -879,9 +1334,9 @@ pub fn build_block_witness<'a>(
                 let swap_witness = SwapWitness::apply_tx(witness_accum.account_tree, &swap);
 
                 let input = (
-                    SigDataInput::from_order(&swap.tx.orders.0)?,
-                    SigDataInput::from_order(&swap.tx.orders.1)?,
-                    SigDataInput::from_swap_op(&swap)?,
+                    SigDataInput::from_order(&swap.tx.orders.0).map_err(invalid_op)?,
+                    SigDataInput::from_order(&swap.tx.orders.1).map_err(invalid_op)?,
+                    SigDataInput::from_swap_op(&swap).map_err(invalid_op)?,
                 );
 
                 let swap_operations = swap_witness.calculate_operations(input);
@@ -899,7 +1354,7 @@ pub fn build_block_witness<'a>(
                 let mint_nft_witness =
                     MintNFTWitness::apply_tx(witness_accum.account_tree, &mint_nft);
 
-                let input = SigDataInput::from_mint_nft_op(&mint_nft)?;
+                let input = SigDataInput::from_mint_nft_op(&mint_nft).map_err(invalid_op)?;
                 let mint_nft_operations = mint_nft_witness.calculate_operations(input);
 
                 operations.extend(mint_nft_operations);
@@ -914,7 +1369,8 @@ pub fn build_block_witness<'a>(
                 let withdraw_nft_witness =
                     WithdrawNFTWitness::apply_tx(witness_accum.account_tree, &withdraw_nft);
 
-                let input = SigDataInput::from_withdraw_nft_op(&withdraw_nft)?;
+                let input =
+                    SigDataInput::from_withdraw_nft_op(&withdraw_nft).map_err(invalid_op)?;
                 let withdraw_nft_operations = withdraw_nft_witness.calculate_operations(input);
 
                 operations.extend(withdraw_nft_operations);
@@ -927,31 +1383,92 @@ pub fn build_block_witness<'a>(
             }
         }
     }
+    check_operations_count(expected_operations, &operations)?;
 
     witness_accum.add_operation_with_pubdata(operations, pub_data, offset_commitment);
+    if options.verify_pubdata {
+        let block_ops: Vec<_> = block
+            .block_transactions
+            .iter()
+            .filter_map(|tx| tx.get_executed_op().cloned())
+            .collect();
+        witness_accum.verify_pubdata(&block_ops)?;
+    }
     witness_accum.extend_pubdata_with_noops(block_size);
-    assert_eq!(witness_accum.pubdata.len(), CHUNK_BIT_WIDTH * block_size);
-    assert_eq!(witness_accum.operations.len(), block_size);
+    witness_accum.check_pubdata_length(block_size)?;
+    anyhow::ensure!(
+        witness_accum.operations.len() == block_size,
+        "Block {} has {} circuit operations, expected {}",
+        block.block_number,
+        witness_accum.operations.len(),
+        block_size
+    );
 
     witness_accum.collect_fees(&fees);
-    assert_eq!(
-        witness_accum
-            .root_after_fees
-            .expect("root_after_fees not present"),
-        block.new_root_hash,
-        "Root hash in witness accumulator doesn't match block root hash. Block: {}",
-        block.block_number
-    );
+    let root_after_fees = witness_accum
+        .root_after_fees
+        .expect("root_after_fees not present");
+    if root_after_fees != block.new_root_hash {
+        if options.check_new_root {
+            return Err(RootMismatch {
+                block_number: block.block_number,
+                expected: block.new_root_hash,
+                actual: root_after_fees,
+            }
+            .into());
+        }
+        vlog::warn!(
+            "Root hash in witness accumulator doesn't match block root hash. Block: {}, witness root: {}, block root: {}",
+            block.block_number,
+            root_after_fees,
+            block.new_root_hash
+        );
+    }
     witness_accum.calculate_pubdata_commitment();
 
     let mut block_commitment = block.block_commitment.as_bytes().to_vec();
     block_commitment[0] &= 0xffu8 >> 3;
     let block_commitment = fr_from_bytes(block_commitment);
-    assert_eq!(
-        witness_accum.pubdata_commitment.unwrap(),
-        block_commitment,
-        "Witness accumulator and server have different commitment. Block: {}",
-        block.block_number
-    );
+    let pubdata_commitment = witness_accum.pubdata_commitment.unwrap();
+    if let Some(expected) = options.expected_commitment {
+        if pubdata_commitment != expected {
+            return Err(CommitmentMismatch {
+                block_number: block.block_number,
+                expected,
+                calculated: pubdata_commitment,
+            }
+            .into());
+        }
+    }
+    if pubdata_commitment != block_commitment {
+        if options.check_commitment {
+            return Err(CommitmentMismatch {
+                block_number: block.block_number,
+                expected: block_commitment,
+                calculated: pubdata_commitment,
+            }
+            .into());
+        }
+        vlog::warn!(
+            "Witness accumulator and server have different commitment. Block: {}",
+            block.block_number
+        );
+    }
     Ok(witness_accum)
 }
+
+/// Checks that there is a circuit operation for every chunk of the executed block operations,
+/// so that an operation silently dropped from the witness is detected before proving.
+pub(crate) fn check_operations_count(
+    expected: usize,
+    operations: &[Operation<Engine>],
+) -> Result<(), anyhow::Error> {
+    if operations.len() != expected {
+        anyhow::bail!(
+            "witness count mismatch: expected {} circuit operations, got {}",
+            expected,
+            operations.len()
+        );
+    }
+    Ok(())
+}
@@ -41,6 +41,25 @@ pub struct WithdrawData {
     pub valid_until: u64,
 }
 
+/// Like `TransferData`, can be obtained from the operation decoded from the public data.
+impl From<&WithdrawOp> for WithdrawData {
+    fn from(withdraw: &WithdrawOp) -> Self {
+        let (valid_from, valid_until) = {
+            let time_range = withdraw.tx.time_range.unwrap_or_default();
+            (time_range.valid_from, time_range.valid_until)
+        };
+        Self {
+            amount: withdraw.tx.amount.to_u128().unwrap(),
+            fee: withdraw.tx.fee.to_u128().unwrap(),
+            token: *withdraw.tx.token,
+            account_address: *withdraw.account_id,
+            eth_address: eth_address_to_fr(&withdraw.tx.to),
+            valid_from,
+            valid_until,
+        }
+    }
+}
+
 pub struct WithdrawWitness<E: RescueEngine> {
     pub before: OperationBranch<E>,
     pub after: OperationBranch<E>,
@@ -55,21 +74,8 @@ impl Witness for WithdrawWitness<Bn256> {
     type CalculateOpsInput = SigDataInput;
 
     fn apply_tx(tree: &mut CircuitAccountTree, withdraw: &WithdrawOp) -> Self {
-        let (valid_from, valid_until) = {
-            let time_range = withdraw.tx.time_range.unwrap_or_default();
-            (time_range.valid_from, time_range.valid_until)
-        };
-        let withdraw_data = WithdrawData {
-            amount: withdraw.tx.amount.to_u128().unwrap(),
-            fee: withdraw.tx.fee.to_u128().unwrap(),
-            token: *withdraw.tx.token,
-            account_address: *withdraw.account_id,
-            eth_address: eth_address_to_fr(&withdraw.tx.to),
-            valid_from,
-            valid_until,
-        };
         // le_bit_vector_into_field_element()
-        Self::apply_data(tree, &withdraw_data)
+        Self::apply_data(tree, &WithdrawData::from(withdraw))
     }
 
     fn get_pubdata(&self) -> Vec<bool> {
@@ -21,6 +21,8 @@ pub struct MiscConfig {
     /// Download setup files from `prover_setup_network_dir` if `prover_download_setup` == 1
     /// or use local files if `prover_download_setup` == 0.
     pub prover_download_setup: bool,
+    /// Whether the prover should verify the created proofs before publishing them.
+    pub prover_self_verify: bool,
     /// Network location of setup files.
     pub prover_setup_network_dir: String,
     /// Used to configure env for docker.
@@ -51,6 +53,7 @@ mod tests {
     fn expected_config() -> MiscConfig {
         MiscConfig {
             prover_download_setup: false,
+            prover_self_verify: true,
             prover_setup_network_dir: "-".into(),
             docker_dummy_prover: false,
             zksync_action: "dont_ask".into(),
@@ -67,6 +70,7 @@ mod tests {
     fn from_env() {
         let config = r#"
 MISC_PROVER_DOWNLOAD_SETUP="false"
+MISC_PROVER_SELF_VERIFY="true"
 MISC_PROVER_SETUP_NETWORK_DIR="-"
 MISC_DOCKER_DUMMY_PROVER="false"
 MISC_ZKSYNC_ACTION="dont_ask"
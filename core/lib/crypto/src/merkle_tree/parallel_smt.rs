@@ -451,6 +451,13 @@ where
         1 << self.tree_depth
     }
 
+    /// Returns the element together with the proof of its existence (see `merkle_path`),
+    /// or `None` if there is no element with such index in the tree.
+    pub fn inclusion_proof(&self, index: u32) -> Option<(&T, Vec<(Hash, bool)>)> {
+        let element = self.get(index)?;
+        Some((element, self.merkle_path(index)))
+    }
+
     /// Creates a proof of existence for a certain element of the tree.
     /// Returned value is a list of pairs, where the first element is
     /// the aggregated coupling hash for current layer, and the second is
@@ -862,6 +869,20 @@ mod tests {
         assert_eq!(tree.root_hash(), 697_516_875);
     }
 
+    /// Checks that the inclusion proof is returned only for the existing elements
+    /// and is verified against the tree root.
+    #[test]
+    fn inclusion_proof_test() {
+        let mut tree = TestSMT::new(3);
+        tree.insert(3, TestLeaf(2));
+
+        let (element, proof) = tree.inclusion_proof(3).expect("Element should be present");
+        assert_eq!(element, &TestLeaf(2));
+        assert!(tree.verify_proof(3, TestLeaf(2), proof));
+
+        assert!(tree.inclusion_proof(1).is_none());
+    }
+
     /// Checks the correctness of the built Merkle proofs
     #[test]
     fn merkle_path_test() {
@@ -199,3 +199,71 @@ impl Default for EncodedSingleProof {
         }
     }
 }
+
+impl EncodedSingleProof {
+    /// Returns the ABI representation of the proof as expected by the contract:
+    /// public inputs followed by the proof elements, both encoded as `uint256[]`.
+    pub fn to_abi_tokens(&self) -> Vec<Token> {
+        let inputs = Token::Array(self.inputs.iter().map(|v| Token::Uint(*v)).collect());
+        let proof = Token::Array(self.proof.iter().map(|v| Token::Uint(*v)).collect());
+        vec![inputs, proof]
+    }
+
+    /// Returns the proof elements (without the public inputs) concatenated as 32-byte
+    /// big-endian words, i.e. the layout of the proof in the contract calldata.
+    pub fn to_flat_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; 32 * self.proof.len()];
+        for (element, word) in self.proof.iter().zip(bytes.chunks_mut(32)) {
+            element.to_big_endian(word);
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoded_single_proof_abi_tokens() {
+        let proof = EncodedSingleProof {
+            inputs: vec![U256::from(1u64)],
+            proof: (0..33u64).map(U256::from).collect(),
+        };
+        let tokens = proof.to_abi_tokens();
+        assert_eq!(tokens.len(), 2);
+
+        match &tokens[0] {
+            Token::Array(inputs) => {
+                assert_eq!(inputs, &vec![Token::Uint(U256::from(1u64))]);
+            }
+            token => panic!("Unexpected token for inputs: {:?}", token),
+        }
+        match &tokens[1] {
+            Token::Array(elements) => {
+                assert_eq!(elements.len(), 33);
+                assert!(elements
+                    .iter()
+                    .enumerate()
+                    .all(|(i, e)| e == &Token::Uint(U256::from(i as u64))));
+            }
+            token => panic!("Unexpected token for proof: {:?}", token),
+        }
+    }
+
+    #[test]
+    fn encoded_single_proof_flat_bytes() {
+        let proof = EncodedSingleProof {
+            inputs: vec![U256::from(1u64)],
+            proof: (0..33u64).map(|i| U256::MAX - U256::from(i)).collect(),
+        };
+        let bytes = proof.to_flat_bytes();
+        assert_eq!(bytes.len(), 32 * 33);
+
+        for (element, word) in proof.proof.iter().zip(bytes.chunks(32)) {
+            let mut expected = [0u8; 32];
+            element.to_big_endian(&mut expected);
+            assert_eq!(word, &expected[..]);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::collections::HashMap;
+use zksync_crypto::proof::SingleProof;
+use zksync_prover_utils::test_data::{empty_block_proof, EmptyBlockProof, EMPTY_BLOCK_CHUNKS};
+use zksync_prover_utils::{verify_block_proof, verify_many, PlonkVerificationKey};
+
+const PROOFS_COUNT: usize = 16;
+
+/// Generates a batch of proofs for the empty block along with the verification keys.
+/// Requires the universal setup and verification keys to be present.
+fn prepare_proofs() -> (
+    Vec<(usize, SingleProof)>,
+    HashMap<usize, PlonkVerificationKey>,
+) {
+    let EmptyBlockProof { vk, proof, .. } = empty_block_proof();
+
+    let proofs = vec![(EMPTY_BLOCK_CHUNKS, proof); PROOFS_COUNT];
+    let mut vks = HashMap::new();
+    vks.insert(EMPTY_BLOCK_CHUNKS, vk);
+    (proofs, vks)
+}
+
+/// Compares the serial verification of the proofs batch with the parallel one.
+fn bench_verify_proofs(c: &mut Criterion) {
+    let (proofs, vks) = prepare_proofs();
+
+    let mut group = c.benchmark_group("Proofs verification");
+    group.sample_size(10);
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            let results: Vec<bool> = proofs
+                .iter()
+                .map(|(block_chunks, proof)| {
+                    verify_block_proof(proof, &vks[block_chunks]).unwrap_or(false)
+                })
+                .collect();
+            black_box(results)
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| black_box(verify_many(&proofs, &vks)))
+    });
+    group.finish();
+}
+
+criterion_group!(verify_benches, bench_verify_proofs);
+criterion_main!(verify_benches);
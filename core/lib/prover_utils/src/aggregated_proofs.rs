@@ -49,11 +49,14 @@ pub fn prepare_proof_data(
     (all_vks, single_proof_data)
 }
 
+/// Generates the recursive proof aggregating the given block proofs.
+/// If `self_verify` is set, the created proof is verified before being returned.
 pub fn gen_aggregate_proof(
     single_vks: Vec<Vk>,
     proofs: Vec<SingleProofData>,
     available_aggregated_proof_sizes: &[(usize, u32)],
     download_setup_network: bool,
+    self_verify: bool,
 ) -> anyhow::Result<AggregatedProof> {
     // proofs: Vec<SingleProofData>,
     let mut individual_vk_inputs = Vec::new();
@@ -135,17 +138,19 @@ pub fn gen_aggregate_proof(
     // save_to_cache_universal_setup_monomial_form(setup_power, universal_setup);
     metrics::histogram!("prover", start.elapsed(), "stage" => "create_proof", "type" => "aggregated_proof");
 
-    let start = Instant::now();
-    let is_valid = verify::<_, _, RollingKeccakTranscript<<Engine as ScalarEngine>::Fr>>(
-        &vk_for_recursive_circuit,
-        &rec_aggr_proof,
-        None,
-    )
-    .expect("must perform verification");
-    metrics::histogram!("prover", start.elapsed(), "stage" => "verify_proof", "type" => "aggregated_proof");
-    if !is_valid {
-        return Err(anyhow::anyhow!("Recursive proof is invalid"));
-    };
+    if self_verify {
+        let start = Instant::now();
+        let is_valid = verify::<_, _, RollingKeccakTranscript<<Engine as ScalarEngine>::Fr>>(
+            &vk_for_recursive_circuit,
+            &rec_aggr_proof,
+            None,
+        )
+        .expect("must perform verification");
+        metrics::histogram!("prover", start.elapsed(), "stage" => "verify_proof", "type" => "aggregated_proof");
+        if !is_valid {
+            return Err(anyhow::anyhow!("Recursive proof is invalid"));
+        };
+    }
 
     Ok(AggregatedProof {
         proof: rec_aggr_proof,
@@ -39,6 +39,9 @@ pub struct ProverOutputRequest {
     pub first_block: BlockNumber,
     pub last_block: BlockNumber,
     pub data: JobResultData,
+    /// Time spent by the prover to generate the proof, in milliseconds.
+    #[serde(default)]
+    pub proving_ms: u64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -54,6 +57,12 @@ pub struct WorkingOn {
     pub job_id: i32,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReleaseJob {
+    pub prover_name: String,
+    pub job_id: i32,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ProverStopped {
     pub prover_name: String,
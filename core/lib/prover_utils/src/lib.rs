@@ -1,5 +1,8 @@
 use lazy_static::lazy_static;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::fs::File;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
@@ -9,8 +12,8 @@ use zksync_crypto::bellman::plonk::better_cs::{
     keys::VerificationKey, verifier::verify,
 };
 use zksync_crypto::bellman::plonk::{
-    commitments::transcript::keccak_transcript::RollingKeccakTranscript, prove_by_steps, setup,
-    transpile,
+    commitments::transcript::keccak_transcript::RollingKeccakTranscript, make_verification_key,
+    prove_by_steps, setup, transpile,
 };
 use zksync_crypto::franklin_crypto::bellman::Circuit;
 use zksync_crypto::franklin_crypto::circuit::test::TestConstraintSystem;
@@ -22,6 +25,7 @@ use zksync_crypto::params::RECURSIVE_CIRCUIT_VK_TREE_DEPTH;
 use zksync_crypto::proof::SingleProof;
 use zksync_crypto::recursive_aggregation_circuit::circuit::create_vks_tree;
 use zksync_crypto::{Engine, Fr};
+use zksync_types::BlockNumber;
 
 use crate::fs_utils::{get_block_verification_key_path, get_exodus_verification_key_path};
 
@@ -30,6 +34,7 @@ pub mod api;
 pub mod exit_proof;
 pub mod fs_utils;
 pub mod network_utils;
+pub mod test_data;
 
 pub const SETUP_MIN_POW2: u32 = 20;
 pub const SETUP_MAX_POW2: u32 = 26;
@@ -102,27 +107,10 @@ impl SetupForStepByStepProver {
         circuit: C,
         vk: &PlonkVerificationKey,
     ) -> Result<SingleProof, anyhow::Error> {
-        let start = Instant::now();
-        let rns_params =
-            RnsParameters::<Engine, <Engine as EngineTrait>::Fq>::new_for_field(68, 110, 4);
-        let rescue_params = Bn256RescueParams::new_checked_2_into_1();
-
-        let transcript_params = (&rescue_params, &rns_params);
-        let proof = prove_by_steps::<_, _, RescueTranscriptForRNS<Engine>>(
-            circuit.clone(),
-            &self.hints,
-            &self.setup_polynomials,
-            None,
-            self.key_monomial_form
-                .as_ref()
-                .expect("Setup should have universal setup struct"),
-            Some(transcript_params),
-        )?;
-        metrics::histogram!("prover", start.elapsed(), "stage" => "create_proof", "type" => "single_proof");
+        let proof = self.gen_unverified_step_by_step_proof_using_prepared_setup(circuit.clone())?;
 
         let start = Instant::now();
-        let valid =
-            verify::<_, _, RescueTranscriptForRNS<Engine>>(&proof, &vk.0, Some(transcript_params))?;
+        let valid = verify_block_proof(&proof, vk)?;
         metrics::histogram!("prover", start.elapsed(), "stage" => "verify_proof", "type" => "single_proof");
         if !valid {
             let start = Instant::now();
@@ -139,8 +127,46 @@ impl SetupForStepByStepProver {
             }
             metrics::histogram!("prover", start.elapsed(), "stage" => "test_constraint_system", "type" => "single_proof");
         }
-        anyhow::ensure!(valid, "proof for block is invalid");
-        Ok(proof.into())
+        anyhow::ensure!(valid, InvalidProofError);
+        Ok(proof)
+    }
+
+    /// Generates the verification key for the circuit this setup was prepared for.
+    pub fn make_verification_key(&self) -> Result<PlonkVerificationKey, anyhow::Error> {
+        let vk = make_verification_key(
+            &self.setup_polynomials,
+            self.key_monomial_form
+                .as_ref()
+                .expect("Setup should have universal setup struct"),
+        )?;
+        Ok(PlonkVerificationKey(vk))
+    }
+
+    /// Generates the proof without verifying it afterwards.
+    /// Verification takes a noticeable time, so it may be skipped if proofs are verified elsewhere.
+    pub fn gen_unverified_step_by_step_proof_using_prepared_setup<C: Circuit<Engine> + Clone>(
+        &self,
+        circuit: C,
+    ) -> Result<SingleProof, anyhow::Error> {
+        let start = Instant::now();
+        let rns_params =
+            RnsParameters::<Engine, <Engine as EngineTrait>::Fq>::new_for_field(68, 110, 4);
+        let rescue_params = Bn256RescueParams::new_checked_2_into_1();
+
+        let transcript_params = (&rescue_params, &rns_params);
+        let proof = prove_by_steps::<_, _, RescueTranscriptForRNS<Engine>>(
+            circuit,
+            &self.hints,
+            &self.setup_polynomials,
+            None,
+            self.key_monomial_form
+                .as_ref()
+                .expect("Setup should have universal setup struct"),
+            Some(transcript_params),
+        )?;
+        metrics::histogram!("prover", start.elapsed(), "stage" => "create_proof", "type" => "single_proof");
+
+        Ok(SingleProof::from(proof))
     }
 }
 
@@ -154,6 +180,100 @@ impl Drop for SetupForStepByStepProver {
     }
 }
 
+/// Error returned when the created block proof fails the self-verification,
+/// which means that the circuit or the witness is broken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidProofError;
+
+impl fmt::Display for InvalidProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "proof for block is invalid")
+    }
+}
+
+impl std::error::Error for InvalidProofError {}
+
+/// Verifies the block proof against the verification key of the main circuit.
+pub fn verify_block_proof(
+    proof: &SingleProof,
+    vk: &PlonkVerificationKey,
+) -> Result<bool, anyhow::Error> {
+    let rns_params =
+        RnsParameters::<Engine, <Engine as EngineTrait>::Fq>::new_for_field(68, 110, 4);
+    let rescue_params = Bn256RescueParams::new_checked_2_into_1();
+    let transcript_params = (&rescue_params, &rns_params);
+
+    let valid =
+        verify::<_, _, RescueTranscriptForRNS<Engine>>(&proof.0, &vk.0, Some(transcript_params))?;
+    Ok(valid)
+}
+
+/// Verifies a batch of block proofs in parallel.
+///
+/// Each proof is paired with the size of the block (in chunks), and `vks` should contain
+/// the verification key for every block size, so that each key is prepared only once.
+/// Proofs that can't be verified (e.g. there is no key for the block size) are considered invalid.
+pub fn verify_many(
+    proofs: &[(usize, SingleProof)],
+    vks: &HashMap<usize, PlonkVerificationKey>,
+) -> Vec<bool> {
+    proofs
+        .par_iter()
+        .map(|(block_chunks, proof)| {
+            vks.get(block_chunks)
+                .map(|vk| verify_block_proof(proof, vk).unwrap_or(false))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Verifier of the block proofs for nodes that don't generate proofs.
+///
+/// Only the verification keys are loaded, so neither the universal setup
+/// nor the account tree is required.
+pub struct ProofVerifier {
+    vks: HashMap<usize, PlonkVerificationKey>,
+}
+
+impl ProofVerifier {
+    /// Loads the verification keys of the main circuit for the provided block sizes (in chunks).
+    pub fn create(block_chunks_sizes: &[usize]) -> Result<Self, anyhow::Error> {
+        let mut vks = HashMap::new();
+        for &block_chunks in block_chunks_sizes {
+            let vk = PlonkVerificationKey::read_verification_key_for_main_circuit(block_chunks)?;
+            vks.insert(block_chunks, vk);
+        }
+        Ok(Self { vks })
+    }
+
+    /// Verifies the proof of the block of `block_chunks` size.
+    pub fn verify(&self, block_chunks: usize, proof: &SingleProof) -> Result<bool, anyhow::Error> {
+        let vk = self.vks.get(&block_chunks).ok_or_else(|| {
+            anyhow::format_err!("No verification key for block size {}", block_chunks)
+        })?;
+        verify_block_proof(proof, vk)
+    }
+}
+
+/// Block proof bundled with the data required to verify it outside of the server,
+/// e.g. to share it with an external verifier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifiableBlockProof {
+    pub block_number: BlockNumber,
+    /// Size of the block in chunks, defines the verification key to use.
+    pub block_chunks: usize,
+    /// Public data of the block. Its commitment is one of the proof inputs.
+    pub public_data: Vec<u8>,
+    pub proof: SingleProof,
+}
+
+impl VerifiableBlockProof {
+    /// Verifies the proof against the verification key for blocks of `block_chunks` size.
+    pub fn verify(&self, vk: &PlonkVerificationKey) -> Result<bool, anyhow::Error> {
+        verify_block_proof(&self.proof, vk)
+    }
+}
+
 /// Generates proof for exit given circuit using step-by-step algorithm.
 pub fn gen_verified_proof_for_exit_circuit<C: Circuit<Engine> + Clone>(
     circuit: C,
@@ -245,3 +365,72 @@ impl UniversalSetupCache {
 lazy_static! {
     static ref UNIVERSAL_SETUP_CACHE: UniversalSetupCache = UniversalSetupCache::new();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::{empty_block_proof, EmptyBlockProof, EMPTY_BLOCK_CHUNKS};
+
+    /// Checks that the parallel verification gives the same results as the serial one.
+    #[test]
+    #[ignore] // Requires the universal setup and verification keys.
+    fn verify_many_matches_serial_verification() {
+        let EmptyBlockProof { vk, proof, .. } = empty_block_proof();
+
+        let mut vks = HashMap::new();
+        vks.insert(EMPTY_BLOCK_CHUNKS, vk);
+        // There is no verification key for the last proof, so it must be considered invalid.
+        let proofs = vec![
+            (EMPTY_BLOCK_CHUNKS, proof.clone()),
+            (EMPTY_BLOCK_CHUNKS, proof.clone()),
+            (32, proof),
+        ];
+
+        let serial: Vec<bool> = proofs
+            .iter()
+            .map(|(block_chunks, proof)| {
+                vks.get(block_chunks)
+                    .map(|vk| verify_block_proof(proof, vk).unwrap())
+                    .unwrap_or(false)
+            })
+            .collect();
+        let parallel = verify_many(&proofs, &vks);
+
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel, vec![true, true, false]);
+    }
+
+    /// Checks that the proof generated by the prover is accepted by the verifier
+    /// which has only the verification keys.
+    #[test]
+    #[ignore] // Requires the universal setup and verification keys.
+    fn proof_verifier_accepts_generated_proof() {
+        let EmptyBlockProof { proof, .. } = empty_block_proof();
+
+        let verifier =
+            ProofVerifier::create(&[EMPTY_BLOCK_CHUNKS]).expect("Failed to create proof verifier");
+        assert!(verifier.verify(EMPTY_BLOCK_CHUNKS, &proof).unwrap());
+        assert!(verifier.verify(32, &proof).is_err());
+    }
+
+    /// Checks that the proof can be verified after the serialization round trip.
+    #[test]
+    #[ignore] // Requires the universal setup and verification keys.
+    fn verifiable_block_proof_round_trip() {
+        let EmptyBlockProof { vk, proof, .. } = empty_block_proof();
+
+        let bundle = VerifiableBlockProof {
+            block_number: BlockNumber(1),
+            block_chunks: EMPTY_BLOCK_CHUNKS,
+            // Block contains only noops.
+            public_data: vec![0u8; EMPTY_BLOCK_CHUNKS * zksync_crypto::params::CHUNK_BYTES],
+            proof,
+        };
+        let serialized = serde_json::to_string(&bundle).unwrap();
+        let deserialized: VerifiableBlockProof = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.block_number, bundle.block_number);
+        assert_eq!(deserialized.public_data, bundle.public_data);
+        assert!(deserialized.verify(&vk).unwrap());
+    }
+}
@@ -0,0 +1,59 @@
+//! Utilities used to generate test data for tests and benchmarks that involve block proofs.
+//! Generating the proofs requires the universal setup and verification keys to be present.
+
+// Workspace uses
+use zksync_circuit::circuit::ZkSyncCircuit;
+use zksync_circuit::witness::utils::WitnessBuilder;
+use zksync_crypto::circuit::{account::CircuitAccount, CircuitAccountTree};
+use zksync_crypto::proof::SingleProof;
+use zksync_crypto::Engine;
+use zksync_types::{Account, AccountId, Address, BlockNumber};
+// Local uses
+use crate::{PlonkVerificationKey, SetupForStepByStepProver};
+
+/// Size of the block created by `empty_block_circuit`.
+pub const EMPTY_BLOCK_CHUNKS: usize = 10;
+
+/// Creates the circuit of the first block, which contains only noops.
+pub fn empty_block_circuit() -> ZkSyncCircuit<'static, Engine> {
+    let mut circuit_account_tree =
+        CircuitAccountTree::new(zksync_crypto::params::account_tree_depth());
+    let fee_account_id = AccountId(0);
+    let fee_account = Account::default_with_address(&Address::default());
+    circuit_account_tree.insert(*fee_account_id, CircuitAccount::from(fee_account));
+
+    let mut witness_accum =
+        WitnessBuilder::new(&mut circuit_account_tree, fee_account_id, BlockNumber(1), 0);
+    witness_accum.extend_pubdata_with_noops(EMPTY_BLOCK_CHUNKS);
+    witness_accum.collect_fees(&[]);
+    witness_accum.calculate_pubdata_commitment();
+    witness_accum.into_circuit_instance()
+}
+
+/// Verified proof of the `empty_block_circuit` along with the data used to create it.
+pub struct EmptyBlockProof {
+    pub circuit: ZkSyncCircuit<'static, Engine>,
+    pub setup: SetupForStepByStepProver,
+    pub vk: PlonkVerificationKey,
+    pub proof: SingleProof,
+}
+
+/// Creates and verifies the proof of the `empty_block_circuit`.
+pub fn empty_block_proof() -> EmptyBlockProof {
+    let circuit = empty_block_circuit();
+    let vk = PlonkVerificationKey::read_verification_key_for_main_circuit(EMPTY_BLOCK_CHUNKS)
+        .expect("Failed to read verification key");
+    let setup =
+        SetupForStepByStepProver::prepare_setup_for_step_by_step_prover(circuit.clone(), false)
+            .expect("Failed to prepare setup");
+    let proof = setup
+        .gen_step_by_step_proof_using_prepared_setup(circuit.clone(), &vk)
+        .expect("Failed to generate proof");
+
+    EmptyBlockProof {
+        circuit,
+        setup,
+        vk,
+        proof,
+    }
+}
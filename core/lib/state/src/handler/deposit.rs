@@ -45,6 +45,9 @@ impl TxHandler<Deposit> for ZkSyncState {
         Ok(result)
     }
 
+    /// Deposits only credit the balance of the recipient. The recipient is identified by
+    /// its address, and the deposit never changes the public key hash of an existing account
+    /// (regardless of the depositor address), so it can only be set via `ChangePubKey`.
     fn apply_op(
         &mut self,
         op: &Self::Op,
@@ -59,6 +62,11 @@ impl TxHandler<Deposit> for ZkSyncState {
         });
 
         let old_amount = account.get_balance(op.priority_op.token);
+        // Balance that doesn't fit into the circuit balance field can't be proven.
+        invariant!(
+            (&old_amount + &op.priority_op.amount).bits() <= params::BALANCE_BIT_WIDTH as u64,
+            DepositOpError::BalanceOverflow
+        );
         let old_nonce = account.nonce;
         account.add_balance(op.priority_op.token, &op.priority_op.amount);
         let new_amount = account.get_balance(op.priority_op.token);
@@ -38,6 +38,8 @@ pub enum CloseOpError {
 pub enum DepositOpError {
     #[error("Deposit token is out of range, this should be enforced by contract")]
     InvalidToken,
+    #[error("Recipient balance exceeds the maximum supported balance")]
+    BalanceOverflow,
 }
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -90,6 +92,8 @@ pub enum TransferOpError {
     NonceMismatch,
     #[error("Not enough balance")]
     InsufficientBalance,
+    #[error("Recipient balance exceeds the maximum supported balance")]
+    BalanceOverflow,
     #[error("Bug: transfer to self should not be called")]
     CannotTransferToSelf,
 }
@@ -201,4 +205,6 @@ pub enum SwapOpError {
     AmountsNotCompatible,
     #[error("Self-swap is not allowed")]
     SelfSwap,
+    #[error("Recipient balance exceeds the maximum supported balance")]
+    BalanceOverflow,
 }
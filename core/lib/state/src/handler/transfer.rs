@@ -121,13 +121,19 @@ impl ZkSyncState {
             TransferOpError::InsufficientBalance
         );
 
+        let to_old_balance = to_account.get_balance(op.tx.token);
+        // Balance that doesn't fit into the circuit balance field can't be proven.
+        invariant!(
+            (&to_old_balance + &op.tx.amount).bits() <= params::BALANCE_BIT_WIDTH as u64,
+            TransferOpError::BalanceOverflow
+        );
+
         from_account.sub_balance(op.tx.token, &(&op.tx.amount + &op.tx.fee));
         *from_account.nonce += 1;
 
         let from_new_balance = from_account.get_balance(op.tx.token);
         let from_new_nonce = from_account.nonce;
 
-        let to_old_balance = to_account.get_balance(op.tx.token);
         let to_account_nonce = to_account.nonce;
 
         to_account.add_balance(op.tx.token, &op.tx.amount);
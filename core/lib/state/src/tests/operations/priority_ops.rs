@@ -1,6 +1,11 @@
-use crate::tests::{AccountState::*, PlasmaTestBuilder};
+use crate::{
+    handler::TxHandler,
+    state::ZkSyncState,
+    tests::{AccountState::*, PlasmaTestBuilder},
+};
 use num::{BigUint, Zero};
 use web3::types::H160;
+use zksync_crypto::params::BALANCE_BIT_WIDTH;
 use zksync_types::{
     account::AccountUpdate,
     priority_ops::{Deposit, FullExit},
@@ -35,6 +40,63 @@ fn deposit_to_existing() {
     )
 }
 
+/// Check that Deposit from a different address to an existing account
+/// doesn't change the public key hash of the account
+#[test]
+fn deposit_to_existing_keeps_pub_key_hash() {
+    let token = TokenId(0);
+    let amount = BigUint::from(100u32);
+    let mut tb = PlasmaTestBuilder::new();
+    let (account_id, account, _) = tb.add_account(Unlocked);
+
+    let deposit = Deposit {
+        from: H160::random(),
+        to: account.address,
+        amount,
+        token,
+    };
+
+    tb.test_priority_op_success(
+        ZkSyncPriorityOp::Deposit(deposit),
+        &[(
+            account_id,
+            AccountUpdate::UpdateBalance {
+                old_nonce: account.nonce,
+                new_nonce: account.nonce,
+                balance_update: (token, BigUint::zero(), BigUint::from(100u32)),
+            },
+        )],
+    );
+    assert_eq!(
+        tb.state.get_account(account_id).unwrap().pub_key_hash,
+        account.pub_key_hash
+    );
+}
+
+/// Check Deposit failure if the recipient balance doesn't fit into the balance field
+#[test]
+fn deposit_recipient_balance_overflow() {
+    let token = TokenId(0);
+    let mut tb = PlasmaTestBuilder::new();
+    let (account_id, account, _) = tb.add_account(Locked);
+    let max_balance = (BigUint::from(1u32) << BALANCE_BIT_WIDTH) - 1u32;
+    tb.set_balance(account_id, token, max_balance);
+
+    let deposit = Deposit {
+        from: account.address,
+        to: account.address,
+        amount: BigUint::from(1u32),
+        token,
+    };
+
+    let error = <ZkSyncState as TxHandler<Deposit>>::apply_tx(&mut tb.state, deposit)
+        .expect_err("deposit didn't fail");
+    assert_eq!(
+        error.to_string(),
+        "Recipient balance exceeds the maximum supported balance"
+    );
+}
+
 /// Check Deposit to new account
 #[test]
 fn deposit_to_new() {
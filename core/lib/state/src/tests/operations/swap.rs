@@ -1,6 +1,6 @@
 use crate::tests::{AccountState::*, PlasmaTestBuilder};
 use num::{BigUint, Zero};
-use zksync_crypto::PrivateKey;
+use zksync_crypto::{params::BALANCE_BIT_WIDTH, PrivateKey};
 use zksync_types::{Account, AccountId, AccountUpdate, Order, Swap, TokenId};
 use TestResult::*;
 
@@ -284,6 +284,41 @@ fn equal_tokens() {
     test_swap.test(tb, Failure("Can't swap the same tokens"));
 }
 
+/// Accounts try to swap, balance of one of the recipients doesn't fit into the balance field, should fail
+#[test]
+fn recipient_balance_overflow() {
+    let mut tb = PlasmaTestBuilder::new();
+
+    let test_swap = TestSwap {
+        accounts: (0, 1),
+        recipients: (2, 3),
+        submitter: 4,
+        tokens: (18, 19),
+        fee_token: 0,
+        amounts: (50, 100),
+        fee: 25,
+        balances: (100, 200, 50),
+        first_price: (1, 2),
+        second_price: (2, 1),
+        is_limit_order: (false, false),
+        test_accounts: vec![
+            tb.add_account(Unlocked),
+            tb.add_account(Unlocked),
+            tb.add_account(Locked),
+            tb.add_account(Unlocked),
+            tb.add_account(Unlocked),
+        ],
+    };
+    let max_balance = (BigUint::from(1u32) << BALANCE_BIT_WIDTH) - 1u32;
+    let recipient_1_id = test_swap.test_accounts[test_swap.recipients.1].0;
+    tb.set_balance(recipient_1_id, TokenId(test_swap.tokens.0), max_balance);
+
+    test_swap.test(
+        tb,
+        Failure("Recipient balance exceeds the maximum supported balance"),
+    );
+}
+
 /// Accounts try to swap, one of them hasn't enough balance, should fail
 #[test]
 fn not_enough_balance() {
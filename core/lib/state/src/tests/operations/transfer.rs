@@ -2,6 +2,7 @@ use crate::tests::{AccountState::*, PlasmaTestBuilder};
 use chrono::Utc;
 use num::{BigUint, Zero};
 use web3::types::H160;
+use zksync_crypto::params::BALANCE_BIT_WIDTH;
 use zksync_types::{AccountId, AccountUpdate, Nonce, SignedZkSyncTx, TokenId, Transfer, ZkSyncTx};
 
 /// Check Transfer operation to existing account
@@ -84,6 +85,41 @@ fn insufficient_funds() {
     tb.test_tx_fail(transfer.into(), "Not enough balance");
 }
 
+/// Check Transfer failure if the recipient balance doesn't fit into the balance field
+#[test]
+fn recipient_balance_overflow() {
+    let token_id = TokenId(0);
+    let amount = BigUint::from(1u32);
+    let fee = BigUint::zero();
+
+    let mut tb = PlasmaTestBuilder::new();
+
+    let (from_account_id, from_account, from_sk) = tb.add_account(Unlocked);
+    tb.set_balance(from_account_id, token_id, amount.clone());
+
+    let (to_account_id, to_account, _to_sk) = tb.add_account(Locked);
+    let max_balance = (BigUint::from(1u32) << BALANCE_BIT_WIDTH) - 1u32;
+    tb.set_balance(to_account_id, token_id, max_balance);
+
+    let transfer = Transfer::new_signed(
+        from_account_id,
+        from_account.address,
+        to_account.address,
+        token_id,
+        amount,
+        fee,
+        from_account.nonce,
+        Default::default(),
+        &from_sk,
+    )
+    .unwrap();
+
+    tb.test_tx_fail(
+        transfer.into(),
+        "Recipient balance exceeds the maximum supported balance",
+    );
+}
+
 /// Check Transfer operation to new account
 #[test]
 fn to_new() {
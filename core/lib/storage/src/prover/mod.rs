@@ -83,7 +83,14 @@ impl<'a, 'c> ProverSchema<'a, 'c> {
         Ok(())
     }
 
-    pub async fn get_idle_prover_job_from_job_queue(&mut self) -> QueryResult<Option<ProverJob>> {
+    /// Takes the next idle job from the queue for the given prover.
+    ///
+    /// Jobs given back to the queue by the same prover (see `release_prover_job`) are skipped
+    /// for a while, so that the prover doesn't get the job it can't prove over and over again.
+    pub async fn get_idle_prover_job_from_job_queue(
+        &mut self,
+        prover_name: &str,
+    ) -> QueryResult<Option<ProverJob>> {
         let start = Instant::now();
         // Select the block to prove.
         let mut transaction = self.0.start_transaction().await?;
@@ -93,11 +100,13 @@ impl<'a, 'c> ProverSchema<'a, 'c> {
             r#"
                 SELECT * FROM prover_job_queue
                 WHERE job_status = $1
+                    AND NOT (updated_by = 'server_release_job_' || $2 AND (now() - INTERVAL '120 seconds') < updated_at)
                 ORDER BY (job_priority, id, first_block)
                 FOR UPDATE SKIP LOCKED
                 LIMIT 1
             "#,
-            ProverJobStatus::Idle.to_number()
+            ProverJobStatus::Idle.to_number(),
+            prover_name
         )
         .fetch_optional(transaction.conn())
         .await?;
@@ -150,6 +159,25 @@ impl<'a, 'c> ProverSchema<'a, 'c> {
         Ok(())
     }
 
+    /// Gives the job taken by the prover back to the queue, e.g. if the prover can't prove it.
+    pub async fn release_prover_job(&mut self, job_id: i32, prover_name: &str) -> QueryResult<()> {
+        let start = Instant::now();
+        sqlx::query!(
+            "UPDATE prover_job_queue
+            SET (job_status, updated_at, updated_by) = ($1, now(), 'server_release_job_' || $2)
+            WHERE id = $3 AND job_status = $4",
+            ProverJobStatus::Idle.to_number(),
+            prover_name,
+            job_id,
+            ProverJobStatus::InProgress.to_number()
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql", start.elapsed(), "prover" => "release_prover_job");
+        Ok(())
+    }
+
     /// Marks the prover as stopped.
     pub async fn record_prover_stop(&mut self, prover_name: &str) -> QueryResult<()> {
         let start = Instant::now();
@@ -12,7 +12,7 @@ use zksync_types::{
     tx::{ChangePubKeyType, TxHash},
     AccountId, Address, BlockNumber, CloseOp, Deposit, DepositOp, FullExit, FullExitOp, MintNFTOp,
     SwapOp, Token, TokenId, TokenKind, TransferOp, TransferToNewOp, WithdrawNFTOp, WithdrawOp,
-    H256,
+    ZkSyncTx, H256,
 };
 // Local imports
 
@@ -474,13 +474,15 @@ impl TransactionsHistoryTestSetup {
     }
 
     fn create_close_tx(&mut self, block_index: Option<u32>) -> ExecutedOperations {
+        let close_tx = self.from_zksync_account.sign_close(None, false);
         let close_op = ZkSyncOp::Close(Box::new(CloseOp {
-            tx: self.from_zksync_account.sign_close(None, false),
+            tx: close_tx.clone(),
             account_id: self.from_zksync_account.get_account_id().unwrap(),
         }));
 
         let executed_close_op = ExecutedTx {
-            signed_tx: close_op.try_get_tx().unwrap().into(),
+            // `try_get_tx` doesn't restore `Close` transactions.
+            signed_tx: ZkSyncTx::Close(Box::new(close_tx)).into(),
             success: true,
             op: Some(close_op),
             fail_reason: None,
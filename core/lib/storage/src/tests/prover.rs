@@ -4,7 +4,10 @@ use once_cell::sync::Lazy;
 use tokio::sync::Mutex;
 // Workspace imports
 use zksync_types::{
-    prover::{ProverJob, ProverJobType},
+    prover::{
+        ProverJob, ProverJobType, AGGREGATED_PROOF_JOB_PRIORITY, SINGLE_PROOF_JOB_PRIORITY,
+        WITHDRAWAL_BLOCK_PROOF_JOB_PRIORITY,
+    },
     BlockNumber,
 };
 // Local imports
@@ -16,7 +19,7 @@ static MUTEX: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
 async fn get_idle_job_from_queue(storage: &mut StorageProcessor<'_>) -> QueryResult<ProverJob> {
     let job = ProverSchema(storage)
-        .get_idle_prover_job_from_job_queue()
+        .get_idle_prover_job_from_job_queue("test_prover")
         .await?;
 
     job.ok_or_else(|| format_err!("expect idle job from job queue"))
@@ -35,6 +38,97 @@ async fn test_prover_job_queue(mut storage: StorageProcessor<'_>) -> QueryResult
     Ok(())
 }
 
+/// Checks that the jobs for blocks withdrawing funds are taken after the aggregated proof jobs
+/// but before the routine ones, while the jobs of the same priority are taken in the order of blocks.
+#[db_test]
+async fn test_prover_job_priority(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    // Lock to prevent database deadlock
+    let _lock = MUTEX.lock().await;
+
+    let priorities = [
+        SINGLE_PROOF_JOB_PRIORITY,
+        SINGLE_PROOF_JOB_PRIORITY,
+        WITHDRAWAL_BLOCK_PROOF_JOB_PRIORITY,
+        WITHDRAWAL_BLOCK_PROOF_JOB_PRIORITY,
+        AGGREGATED_PROOF_JOB_PRIORITY,
+    ];
+    for (block_number, priority) in (1..).zip(priorities.iter()) {
+        let job_type = if *priority == AGGREGATED_PROOF_JOB_PRIORITY {
+            ProverJobType::AggregatedProof
+        } else {
+            ProverJobType::SingleProof
+        };
+        ProverSchema(&mut storage)
+            .add_prover_job_to_job_queue(
+                BlockNumber(block_number),
+                BlockNumber(block_number),
+                Default::default(),
+                *priority,
+                job_type,
+            )
+            .await?;
+    }
+
+    let mut proven_blocks = Vec::new();
+    for _ in 0..priorities.len() {
+        proven_blocks.push(get_idle_job_from_queue(&mut storage).await?.first_block);
+    }
+    assert_eq!(
+        proven_blocks,
+        vec![
+            BlockNumber(5),
+            BlockNumber(3),
+            BlockNumber(4),
+            BlockNumber(1),
+            BlockNumber(2)
+        ]
+    );
+
+    Ok(())
+}
+
+/// Checks that the job released by the prover is given back to the queue, but not to the same prover
+/// right away.
+#[db_test]
+async fn test_release_prover_job(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    // Lock to prevent database deadlock
+    let _lock = MUTEX.lock().await;
+
+    ProverSchema(&mut storage)
+        .add_prover_job_to_job_queue(
+            BlockNumber(1),
+            BlockNumber(1),
+            Default::default(),
+            SINGLE_PROOF_JOB_PRIORITY,
+            ProverJobType::SingleProof,
+        )
+        .await?;
+
+    let job = get_idle_job_from_queue(&mut storage).await?;
+    ProverSchema(&mut storage)
+        .record_prover_is_working(job.job_id, "test_prover")
+        .await?;
+    ProverSchema(&mut storage)
+        .release_prover_job(job.job_id, "test_prover")
+        .await?;
+    assert_eq!(ProverSchema(&mut storage).pending_jobs_count().await?, 1);
+
+    // The prover that released the job doesn't get it again.
+    let job_for_same_prover = ProverSchema(&mut storage)
+        .get_idle_prover_job_from_job_queue("test_prover")
+        .await?;
+    assert!(job_for_same_prover.is_none());
+
+    // Another prover does.
+    let job_for_other_prover = ProverSchema(&mut storage)
+        .get_idle_prover_job_from_job_queue("other_prover")
+        .await?
+        .ok_or_else(|| format_err!("expect released job in job queue"))?;
+    assert_eq!(job_for_other_prover.job_id, job.job_id);
+
+    Ok(())
+}
+
 /// Checks that the single and aggregated proof can be stored and loaded.
 async fn test_store_proof(storage: &mut StorageProcessor<'_>) -> QueryResult<()> {
     // Attempt to load the proof that was not stored should result in None.
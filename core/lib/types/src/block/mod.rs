@@ -8,6 +8,7 @@ use parity_crypto::digest::sha256;
 use parity_crypto::Keccak256;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
+use thiserror::Error;
 use zksync_basic_types::{H256, U256};
 use zksync_crypto::franklin_crypto::bellman::pairing::ff::{PrimeField, PrimeFieldRepr};
 use zksync_crypto::params::{CHUNK_BIT_WIDTH, CHUNK_BYTES};
@@ -475,6 +476,18 @@ impl Block {
         H256::from_slice(&sha256(&hash_arg))
     }
 
+    /// Returns the earliest `valid_until` among the successfully executed L2 transactions
+    /// of the block, i.e. the earliest expiry time represented in the block.
+    /// Returns `None` if there are no such transactions.
+    pub fn min_valid_until(&self) -> Option<u64> {
+        self.block_transactions
+            .iter()
+            .filter_map(ExecutedOperations::get_executed_tx)
+            .filter(|tx| tx.success)
+            .map(|tx| tx.signed_tx.tx.time_range().valid_until)
+            .min()
+    }
+
     pub fn processable_ops_pubdata(&self) -> Vec<Vec<u8>> {
         self.block_transactions
             .iter()
@@ -517,6 +530,130 @@ pub fn smallest_block_size_for_chunks(
     );
 }
 
+/// Error returned by `CommitmentBuilder::push_op` if the operation doesn't fit into the block.
+#[derive(Debug, Error, PartialEq)]
+#[error("Operation takes {op_chunks} chunks, but only {free_chunks} chunks are left in the block")]
+pub struct BlockChunksOverflow {
+    pub op_chunks: usize,
+    pub free_chunks: usize,
+}
+
+/// Calculates the block commitment from the operations fed one at a time, so that the operations
+/// list doesn't have to be assembled before the commitment is known.
+#[derive(Debug, Clone)]
+pub struct CommitmentBuilder {
+    block_chunks_size: usize,
+    public_data: Vec<u8>,
+    /// One byte per chunk, set for the first chunk of each on-chain operation.
+    onchain_op_commitment: Vec<u8>,
+}
+
+impl CommitmentBuilder {
+    /// Creates the builder for the block of `block_chunks_size` chunks.
+    pub fn new(block_chunks_size: usize) -> Self {
+        Self {
+            block_chunks_size,
+            public_data: Vec::new(),
+            onchain_op_commitment: Vec::new(),
+        }
+    }
+
+    /// Appends the next executed operation of the block.
+    ///
+    /// The operation is rejected and the builder is left intact if it doesn't fit into the block.
+    pub fn push_op(&mut self, op: &ZkSyncOp) -> Result<(), BlockChunksOverflow> {
+        let first_chunk = self.onchain_op_commitment.len();
+        let free_chunks = self.block_chunks_size - first_chunk;
+        if op.chunks() > free_chunks {
+            return Err(BlockChunksOverflow {
+                op_chunks: op.chunks(),
+                free_chunks,
+            });
+        }
+
+        self.onchain_op_commitment
+            .resize(first_chunk + op.chunks(), 0x00);
+        if op.is_onchain_operation() {
+            self.onchain_op_commitment[first_chunk] = 0x01;
+        }
+        self.public_data.extend(op.public_data());
+        Ok(())
+    }
+
+    /// Returns the number of chunks taken by the operations appended so far.
+    pub fn chunks_used(&self) -> usize {
+        self.onchain_op_commitment.len()
+    }
+
+    /// Pads the appended operations with noops up to the block size and returns the block
+    /// commitment, the same as `Block::block_commitment` of the block with these operations.
+    ///
+    /// `previous_block_root_hash` is the Ethereum-encoded root hash of the previous block.
+    pub fn finalize(
+        mut self,
+        block_number: BlockNumber,
+        fee_account: AccountId,
+        previous_block_root_hash: H256,
+        new_root_hash: Fr,
+        timestamp: u64,
+    ) -> H256 {
+        self.public_data
+            .resize(self.block_chunks_size * CHUNK_BIT_WIDTH / 8, 0x00);
+        self.onchain_op_commitment
+            .resize(self.block_chunks_size, 0x00);
+        Block::get_commitment(
+            block_number,
+            fee_account,
+            previous_block_root_hash,
+            Block::encode_fr_for_eth(new_root_hash),
+            timestamp,
+            &self.onchain_op_commitment,
+            &self.public_data,
+        )
+    }
+}
+
+/// Checks that the public data matches the expected block commitment (e.g. the one stored
+/// on-chain) without any proving. The commitment is recomputed from the provided block header
+/// fields and the public data only: the on-chain operations are located by decoding the public
+/// data, so no `Block` is required.
+///
+/// `previous_block_root_hash` is the Ethereum-encoded root hash of the previous block, and
+/// `public_data` is the public data of the whole block including the noop padding (see
+/// `Block::get_eth_public_data`). Public data that cannot be decoded never matches.
+pub fn verify_pubdata_against_commitment(
+    block_number: BlockNumber,
+    fee_account: AccountId,
+    previous_block_root_hash: H256,
+    new_root_hash: Fr,
+    timestamp: u64,
+    public_data: &[u8],
+    expected_commitment: &H256,
+) -> bool {
+    if public_data.len() % CHUNK_BYTES != 0 {
+        return false;
+    }
+    let ops = match ZkSyncOp::parse_pubdata_blob(public_data) {
+        Ok(ops) => ops,
+        Err(_) => return false,
+    };
+
+    let mut builder = CommitmentBuilder::new(public_data.len() / CHUNK_BYTES);
+    for op in &ops {
+        if builder.push_op(op).is_err() {
+            return false;
+        }
+    }
+    let commitment = builder.finalize(
+        block_number,
+        fee_account,
+        previous_block_root_hash,
+        new_root_hash,
+        timestamp,
+    );
+    commitment == *expected_commitment
+}
+
 #[derive(Debug, Clone)]
 pub struct OnchainOperationsBlockInfo {
     pub public_data_offset: u32,
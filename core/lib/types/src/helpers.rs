@@ -1,8 +1,11 @@
-use num::{BigUint, FromPrimitive};
+use num::{BigInt, BigUint, FromPrimitive};
+use once_cell::sync::OnceCell;
+use std::collections::BTreeMap;
+use zksync_basic_types::{AccountId, TokenId};
 use zksync_crypto::params;
 use zksync_crypto::primitives::FloatConversions;
 
-use crate::{Account, AccountMap, AccountUpdates};
+use crate::{Account, AccountMap, AccountTree, AccountUpdate, AccountUpdates, Fr};
 
 /// Given the account map, applies a sequence of updates to the state.
 pub fn apply_updates(accounts: &mut AccountMap, updates: AccountUpdates) {
@@ -23,6 +26,55 @@ pub fn reverse_updates(updates: &mut AccountUpdates) {
     }
 }
 
+/// Net change of the account state caused by a sequence of updates.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountDelta {
+    /// Net change of the balance of every token touched by the updates.
+    pub balances: BTreeMap<TokenId, BigInt>,
+    /// Net change of the nonce.
+    pub nonce: i64,
+}
+
+/// Folds a sequence of updates into the net balance and nonce changes of every touched account.
+/// Unlike the updates themselves, the result doesn't contain intermediate states, so it's
+/// much more compact, e.g. to be sent to light clients.
+pub fn state_diff(updates: &[(AccountId, AccountUpdate)]) -> BTreeMap<AccountId, AccountDelta> {
+    let mut diff = BTreeMap::<AccountId, AccountDelta>::new();
+    for (id, update) in updates {
+        let delta = diff.entry(*id).or_default();
+        match update {
+            AccountUpdate::UpdateBalance {
+                old_nonce,
+                new_nonce,
+                balance_update: (token, old_balance, new_balance),
+            } => {
+                *delta.balances.entry(*token).or_default() +=
+                    BigInt::from(new_balance.clone()) - BigInt::from(old_balance.clone());
+                delta.nonce += i64::from(**new_nonce) - i64::from(**old_nonce);
+            }
+            AccountUpdate::ChangePubKeyHash {
+                old_nonce,
+                new_nonce,
+                ..
+            } => {
+                delta.nonce += i64::from(**new_nonce) - i64::from(**old_nonce);
+            }
+            AccountUpdate::Create { .. }
+            | AccountUpdate::Delete { .. }
+            | AccountUpdate::MintNFT { .. }
+            | AccountUpdate::RemoveNFT { .. } => {}
+        }
+    }
+    diff
+}
+
+/// Returns the root hash of the account tree without any accounts, i.e. the genesis root.
+/// The hash is calculated once, on the first call.
+pub fn empty_tree_root() -> Fr {
+    static EMPTY_TREE_ROOT: OnceCell<Fr> = OnceCell::new();
+    *EMPTY_TREE_ROOT.get_or_init(|| AccountTree::new(params::account_tree_depth()).root_hash())
+}
+
 /// Transforms the token amount into packed form.
 /// If the provided token amount is not packable, it is rounded down to the
 /// closest amount that fits in packed form. As a result, some precision will be lost.
@@ -156,6 +208,44 @@ mod test {
         }
     }
 
+    #[test]
+    fn state_diff_of_transfer() {
+        use crate::Nonce;
+
+        let (sender, recipient) = (AccountId(1), AccountId(2));
+        let balance_update = |old_nonce, new_nonce, old_balance: u32, new_balance: u32| {
+            AccountUpdate::UpdateBalance {
+                old_nonce: Nonce(old_nonce),
+                new_nonce: Nonce(new_nonce),
+                balance_update: (TokenId(0), old_balance.into(), new_balance.into()),
+            }
+        };
+        // Transfer of 100 tokens with the fee of 10 tokens, followed by the sender
+        // receiving a deposit of 5 tokens.
+        let updates = vec![
+            (sender, balance_update(3, 4, 150, 40)),
+            (recipient, balance_update(0, 0, 0, 100)),
+            (sender, balance_update(4, 4, 40, 45)),
+        ];
+
+        let diff = state_diff(&updates);
+        assert_eq!(diff.len(), 2);
+        assert_eq!(diff[&sender].balances[&TokenId(0)], BigInt::from(-105));
+        assert_eq!(diff[&sender].nonce, 1);
+        assert_eq!(diff[&recipient].balances[&TokenId(0)], BigInt::from(100));
+        assert_eq!(diff[&recipient].nonce, 0);
+    }
+
+    #[test]
+    fn empty_tree_root_matches_new_tree() {
+        let root = empty_tree_root();
+        assert_eq!(
+            root,
+            AccountTree::new(params::account_tree_depth()).root_hash()
+        );
+        assert_eq!(empty_tree_root(), root);
+    }
+
     #[test]
     fn detect_unpackable() {
         let max_mantissa_token =
@@ -1,6 +1,6 @@
 use crate::{
     helpers::{pack_fee_amount, unpack_fee_amount},
-    operations::error::ChangePubkeyOpError,
+    operations::{ensure_zero_padding, error::ChangePubkeyOpError},
     tx::ChangePubKey,
     AccountId, Address, Nonce, PubKeyHash, TokenId,
 };
@@ -23,6 +23,7 @@ pub struct ChangePubKeyOp {
 
 impl ChangePubKeyOp {
     pub const CHUNKS: usize = 6;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x07;
 
     pub fn get_public_data(&self) -> Vec<u8> {
@@ -33,7 +34,7 @@ impl ChangePubKeyOp {
         data.extend_from_slice(&self.tx.nonce.to_be_bytes());
         data.extend_from_slice(&self.tx.fee_token.to_be_bytes());
         data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
@@ -80,6 +81,9 @@ impl ChangePubKeyOp {
             .ok_or(ChangePubkeyOpError::CannotGetFeeTokenId)?;
         let fee =
             unpack_fee_amount(&bytes[fee_offset..end]).ok_or(ChangePubkeyOpError::CannotGetFee)?;
+        if !ensure_zero_padding(bytes, end, bytes.len()) {
+            return Err(ChangePubkeyOpError::NonZeroPadding);
+        }
 
         Ok(ChangePubKeyOp {
             tx: ChangePubKey::new(
@@ -1,4 +1,8 @@
-use crate::{operations::error::CloseOpError, tx::TxSignature, AccountId, Address, Close, Nonce};
+use crate::{
+    operations::{ensure_zero_padding, error::CloseOpError},
+    tx::TxSignature,
+    AccountId, Address, Close, Nonce,
+};
 use serde::{Deserialize, Serialize};
 use zksync_crypto::{
     params::{ACCOUNT_ID_BIT_WIDTH, CHUNK_BYTES, LEGACY_CHUNK_BYTES},
@@ -13,12 +17,13 @@ pub struct CloseOp {
 
 impl CloseOp {
     pub const CHUNKS: usize = 1;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x04;
 
     pub(crate) fn get_public_data(&self) -> Vec<u8> {
         let mut data = vec![Self::OP_CODE];
         data.extend_from_slice(&self.account_id.to_be_bytes());
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
@@ -40,6 +45,13 @@ impl CloseOp {
             &bytes[account_id_offset..account_id_offset + ACCOUNT_ID_BIT_WIDTH / 8],
         )
         .ok_or(CloseOpError::CannotGetFromAccountId)?;
+        if !ensure_zero_padding(
+            bytes,
+            account_id_offset + ACCOUNT_ID_BIT_WIDTH / 8,
+            bytes.len(),
+        ) {
+            return Err(CloseOpError::NonZeroPadding);
+        }
         let account_address = Address::zero(); // From pubdata it is unknown
         let nonce = 0; // From pubdata it is unknown
         let signature = TxSignature::default(); // From pubdata it is unknown
@@ -1,4 +1,7 @@
-use crate::{operations::error::DepositOpError, AccountId, Address, Deposit, TokenId};
+use crate::{
+    operations::{ensure_zero_padding, error::DepositOpError},
+    AccountId, Address, Deposit, TokenId,
+};
 use num::{BigUint, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use zksync_crypto::{
@@ -18,6 +21,7 @@ pub struct DepositOp {
 
 impl DepositOp {
     pub const CHUNKS: usize = 6;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x01;
 
     pub fn get_public_data(&self) -> Vec<u8> {
@@ -26,7 +30,7 @@ impl DepositOp {
         data.extend_from_slice(&self.priority_op.token.to_be_bytes());
         data.extend_from_slice(&self.priority_op.amount.to_u128().unwrap().to_be_bytes());
         data.extend_from_slice(self.priority_op.to.as_bytes());
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
@@ -65,9 +69,15 @@ impl DepositOp {
         let to = Address::from_slice(
             &bytes[account_address_offset..account_address_offset + FR_ADDRESS_LEN],
         );
+        if !ensure_zero_padding(bytes, account_address_offset + FR_ADDRESS_LEN, bytes.len()) {
+            return Err(DepositOpError::NonZeroPadding);
+        }
 
         let from = Address::default(); // unknown from pubdata.
 
+        // Deposits to any account are accepted, including the reserved ones (see `is_reserved_account`):
+        // they're sent from L1, so the account can't be checked in advance.
+
         Ok(Self {
             priority_op: Deposit {
                 from,
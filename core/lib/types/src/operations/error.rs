@@ -15,6 +15,8 @@ pub enum ChangePubkeyOpError {
     CannotGetFeeTokenId,
     #[error("Failed to get fee")]
     CannotGetFee,
+    #[error("Non-zero padding in change pubkey pubdata")]
+    NonZeroPadding,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -23,6 +25,8 @@ pub enum CloseOpError {
     PubdataSizeMismatch,
     #[error("Failed to get from account id")]
     CannotGetFromAccountId,
+    #[error("Non-zero padding in close pubdata")]
+    NonZeroPadding,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -35,6 +39,8 @@ pub enum DepositOpError {
     CannotGetTokenId,
     #[error("Failed to get amount")]
     CannotGetAmount,
+    #[error("Non-zero padding in deposit pubdata")]
+    NonZeroPadding,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -51,6 +57,10 @@ pub enum ForcedExitOpError {
     CannotGetAmount,
     #[error("Failed to get fee")]
     CannotGetFee,
+    #[error("Forced exit of the reserved account")]
+    ReservedAccount,
+    #[error("Non-zero padding in forced exit pubdata")]
+    NonZeroPadding,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -63,6 +73,8 @@ pub enum FullExitOpError {
     CannotGetTokenId,
     #[error("Failed to get amount")]
     CannotGetAmount,
+    #[error("Non-zero padding in full exit pubdata")]
+    NonZeroPadding,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -85,6 +97,14 @@ pub enum TransferOpError {
     CannotGetAmount,
     #[error("Failed to get fee")]
     CannotGetFee,
+    #[error("Amount cannot be packed without precision loss")]
+    AmountNotPackable,
+    #[error("Fee cannot be packed without precision loss")]
+    FeeNotPackable,
+    #[error("Transfer from the reserved account")]
+    ReservedAccount,
+    #[error("Non-zero padding in transfer pubdata")]
+    NonZeroPadding,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -99,6 +119,10 @@ pub enum WithdrawOpError {
     CannotGetAmount,
     #[error("Failed to get fee")]
     CannotGetFee,
+    #[error("Withdrawal from the reserved account")]
+    ReservedAccount,
+    #[error("Non-zero padding in withdraw pubdata")]
+    NonZeroPadding,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -119,6 +143,8 @@ pub enum WithdrawNFTOpError {
     CannotGetAmount,
     #[error("Failed to get fee")]
     CannotGetFee,
+    #[error("Non-zero padding in withdraw NFT pubdata")]
+    NonZeroPadding,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -139,6 +165,8 @@ pub enum MintNFTOpError {
     RecipientAccountId,
     #[error("Cannot parse fee")]
     Fee,
+    #[error("Non-zero padding in mint NFT pubdata")]
+    NonZeroPadding,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -147,6 +175,12 @@ pub enum PublicDataDecodeError {
     EmptyData,
     #[error("Unknown operation type")]
     UnknownOperationType,
+    #[error("Public data ends in the middle of an operation")]
+    TruncatedData,
+    #[error("Declared {declared} chunks, but the operations take {actual} chunks")]
+    ChunksCountMismatch { declared: usize, actual: usize },
+    #[error("Failed to read public data: {0}")]
+    ReadError(String),
     #[error(transparent)]
     ChangePubkeyOpError(#[from] ChangePubkeyOpError),
     #[error(transparent)]
@@ -171,10 +205,32 @@ pub enum PublicDataDecodeError {
     WithdrawNFTOpError(#[from] WithdrawNFTOpError),
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum FramedOpDecodeError {
+    #[error("Framed operation ends in the middle")]
+    TruncatedData,
+    #[error("Framed operation is too large: {0} bytes")]
+    FrameTooLarge(usize),
+    #[error("Failed to read framed operation: {0}")]
+    ReadError(String),
+    #[error("Cannot decode framed operation: {0}")]
+    InvalidOperation(String),
+    #[error("Op code in the frame doesn't match the operation")]
+    OpCodeMismatch,
+}
+
 #[derive(Debug, Error, PartialEq)]
 #[error("Wrong operation type")]
 pub struct UnexpectedOperationType();
 
+#[derive(Debug, Error, PartialEq)]
+pub enum OpFromTxError {
+    #[error("Operation can't be created from this transaction type")]
+    UnsupportedTxType,
+    #[error("Recipient account id is required for this transaction")]
+    MissingRecipient,
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum SwapOpError {
     #[error("Wrong bytes length for swap pubdata")]
@@ -187,4 +243,6 @@ pub enum SwapOpError {
     CannotGetAmount,
     #[error("Failed to get Fee")]
     CannotGetFee,
+    #[error("Non-zero padding in swap pubdata")]
+    NonZeroPadding,
 }
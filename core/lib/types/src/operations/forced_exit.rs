@@ -1,6 +1,6 @@
 use crate::{
     helpers::{pack_fee_amount, unpack_fee_amount},
-    operations::error::ForcedExitOpError,
+    operations::{ensure_zero_padding, error::ForcedExitOpError, is_reserved_account},
     AccountId, Address, ForcedExit, Nonce, TokenId,
 };
 use num::{BigUint, FromPrimitive, ToPrimitive};
@@ -27,6 +27,7 @@ pub struct ForcedExitOp {
 
 impl ForcedExitOp {
     pub const CHUNKS: usize = 6;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x08;
     pub const WITHDRAW_DATA_PREFIX: [u8; 1] = [1];
 
@@ -45,7 +46,7 @@ impl ForcedExitOp {
         data.extend_from_slice(&self.amount().to_be_bytes());
         data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
         data.extend_from_slice(self.tx.target.as_bytes());
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
@@ -87,6 +88,9 @@ impl ForcedExitOp {
                 .ok_or(ForcedExitOpError::CannotGetInitiatorAccountId)?;
         let target_account_id = u32::from_bytes(&bytes[target_account_id_offset..token_id_offset])
             .ok_or(ForcedExitOpError::CannotGetTargetAccountId)?;
+        if is_reserved_account(AccountId(target_account_id)) {
+            return Err(ForcedExitOpError::ReservedAccount);
+        }
         let token = u32::from_bytes(&bytes[token_id_offset..amount_offset])
             .ok_or(ForcedExitOpError::CannotGetTokenId)?;
         let amount = BigUint::from_u128(
@@ -97,6 +101,9 @@ impl ForcedExitOp {
         let fee = unpack_fee_amount(&bytes[fee_offset..eth_address_offset])
             .ok_or(ForcedExitOpError::CannotGetFee)?;
         let target = Address::from_slice(&bytes[eth_address_offset..eth_address_end]);
+        if !ensure_zero_padding(bytes, eth_address_end, bytes.len()) {
+            return Err(ForcedExitOpError::NonZeroPadding);
+        }
 
         let nonce = 0; // From pubdata it is unknown
         let time_range = Default::default();
@@ -2,12 +2,16 @@ use num::{BigUint, FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use zksync_crypto::params::{
     ACCOUNT_ID_BIT_WIDTH, ADDRESS_WIDTH, BALANCE_BIT_WIDTH, CHUNK_BYTES, CONTENT_HASH_WIDTH,
-    ETH_ADDRESS_BIT_WIDTH, LEGACY_CHUNK_BYTES, LEGACY_TOKEN_BIT_WIDTH, TOKEN_BIT_WIDTH,
+    ETH_ADDRESS_BIT_WIDTH, LEGACY_CHUNK_BYTES, LEGACY_TOKEN_BIT_WIDTH, SERIAL_ID_WIDTH,
+    TOKEN_BIT_WIDTH,
 };
 use zksync_crypto::primitives::FromBytes;
 use zksync_utils::BigUintSerdeWrapper;
 
-use crate::{operations::error::FullExitOpError, AccountId, Address, FullExit, TokenId, H256};
+use crate::{
+    operations::{ensure_zero_padding, error::FullExitOpError},
+    AccountId, Address, FullExit, TokenId, H256,
+};
 
 /// FullExit operation. For details, see the documentation of [`ZkSyncOp`](./operations/enum.ZkSyncOp.html).
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +28,7 @@ pub struct FullExitOp {
 impl FullExitOp {
     pub const LEGACY_CHUNKS: usize = 6;
     pub const CHUNKS: usize = 11;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x06;
     pub const WITHDRAW_DATA_PREFIX: [u8; 1] = [0];
 
@@ -54,7 +59,7 @@ impl FullExitOp {
         data.extend_from_slice(self.creator_address.unwrap_or_default().as_bytes());
         data.extend_from_slice(&self.serial_id.unwrap_or_default().to_be_bytes());
         data.extend_from_slice(self.content_hash.unwrap_or_default().as_bytes());
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
@@ -76,7 +81,7 @@ impl FullExitOp {
     }
 
     pub fn from_public_data(bytes: &[u8]) -> Result<Self, FullExitOpError> {
-        if bytes.len() != Self::CHUNKS * CHUNK_BYTES {
+        if bytes.len() != Self::BYTE_SIZE {
             return Err(FullExitOpError::PubdataSizeMismatch);
         }
 
@@ -104,6 +109,17 @@ impl FullExitOp {
             &bytes[content_hash_offset..content_hash_offset + CONTENT_HASH_WIDTH / 8],
         );
 
+        // The NFT fields occupy the bytes right after the amount, see `get_public_data`.
+        let data_end = amount_offset
+            + BALANCE_BIT_WIDTH / 8
+            + ACCOUNT_ID_BIT_WIDTH / 8
+            + ADDRESS_WIDTH / 8
+            + SERIAL_ID_WIDTH / 8
+            + CONTENT_HASH_WIDTH / 8;
+        if !ensure_zero_padding(bytes, data_end, bytes.len()) {
+            return Err(FullExitOpError::NonZeroPadding);
+        }
+
         Ok(Self {
             priority_op: FullExit {
                 account_id: AccountId(account_id),
@@ -139,6 +155,9 @@ impl FullExitOp {
                 .ok_or(FullExitOpError::CannotGetAmount)?,
         )
         .unwrap();
+        if !ensure_zero_padding(bytes, amount_offset + BALANCE_BIT_WIDTH / 8, bytes.len()) {
+            return Err(FullExitOpError::NonZeroPadding);
+        }
 
         Ok(Self {
             priority_op: FullExit {
@@ -9,7 +9,7 @@ use zksync_crypto::{
 };
 
 use crate::helpers::{pack_fee_amount, unpack_fee_amount};
-use crate::operations::error::MintNFTOpError;
+use crate::operations::{ensure_zero_padding, error::MintNFTOpError};
 use crate::{AccountId, Address, MintNFT, Nonce, TokenId, H256};
 
 /// Deposit operation. For details, see the documentation of [`ZkSyncOp`](./operations/enum.ZkSyncOp.html).
@@ -22,6 +22,7 @@ pub struct MintNFTOp {
 
 impl MintNFTOp {
     pub const CHUNKS: usize = 5;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x09;
 
     pub fn get_public_data(&self) -> Vec<u8> {
@@ -31,12 +32,12 @@ impl MintNFTOp {
         data.extend_from_slice(self.tx.content_hash.as_bytes());
         data.extend_from_slice(&self.tx.fee_token.to_be_bytes());
         data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
     pub fn from_public_data(bytes: &[u8]) -> Result<Self, MintNFTOpError> {
-        if bytes.len() != Self::CHUNKS * CHUNK_BYTES {
+        if bytes.len() != Self::BYTE_SIZE {
             return Err(MintNFTOpError::WrongNumberOfBytes);
         }
 
@@ -74,6 +75,14 @@ impl MintNFTOp {
         )
         .ok_or(MintNFTOpError::Fee)?;
 
+        if !ensure_zero_padding(
+            bytes,
+            fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8,
+            bytes.len(),
+        ) {
+            return Err(MintNFTOpError::NonZeroPadding);
+        }
+
         let nonce = 0; // It is unknown from pubdata
 
         Ok(Self {
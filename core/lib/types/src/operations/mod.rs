@@ -1,16 +1,26 @@
 //! Set of all the operations supported by the zkSync network.
 
 use super::ZkSyncTx;
-use crate::ZkSyncPriorityOp;
+use crate::{Account, ZkSyncPriorityOp};
 use num::BigUint;
 use serde::{Deserialize, Serialize};
-use zksync_basic_types::{AccountId, TokenId};
-use zksync_crypto::params::{CHUNK_BYTES, LEGACY_CHUNK_BYTES};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use zksync_basic_types::{AccountId, Address, TokenId};
+use zksync_crypto::params::{
+    ACCOUNT_ID_BIT_WIDTH, ADDRESS_WIDTH, AMOUNT_EXPONENT_BIT_WIDTH, AMOUNT_MANTISSA_BIT_WIDTH,
+    BALANCE_BIT_WIDTH, CHUNK_BYTES, CONTENT_HASH_WIDTH, ETH_ADDRESS_BIT_WIDTH,
+    FEE_EXPONENT_BIT_WIDTH, FEE_MANTISSA_BIT_WIDTH, FR_ADDRESS_LEN, LEGACY_CHUNK_BYTES,
+    NEW_PUBKEY_HASH_WIDTH, NFT_STORAGE_ACCOUNT_ID, NONCE_BIT_WIDTH, SERIAL_ID_WIDTH,
+    TOKEN_BIT_WIDTH,
+};
+use zksync_crypto::primitives::FromBytes;
 
 mod change_pubkey_op;
 mod close_op;
 mod deposit_op;
-mod error;
+pub(crate) mod error;
 mod forced_exit;
 mod full_exit_op;
 mod mint_nft_op;
@@ -29,7 +39,13 @@ pub use self::{
     transfer_op::TransferOp, transfer_to_new_op::TransferToNewOp, withdraw_nft_op::WithdrawNFTOp,
     withdraw_op::WithdrawOp,
 };
-use crate::operations::error::{PublicDataDecodeError, UnexpectedOperationType};
+use crate::operations::error::{
+    FramedOpDecodeError, OpFromTxError, PublicDataDecodeError, UnexpectedOperationType,
+};
+
+/// Maximum size of the encoded operation in the frame written by `ZkSyncOp::write_framed`.
+/// Encoded operations are a few kilobytes at most, so larger frames are considered corrupted.
+pub const MAX_FRAMED_OP_SIZE: usize = 64 * 1024;
 
 /// zkSync network operation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +71,11 @@ pub enum ZkSyncOp {
 }
 
 impl ZkSyncOp {
+    /// Creates `n` noop operations, e.g. to fill the unused block capacity.
+    pub fn noops(n: usize) -> Vec<Self> {
+        vec![Self::Noop(NoopOp::new()); n]
+    }
+
     /// Returns the number of block chunks required for the operation.
     pub fn chunks(&self) -> usize {
         match self {
@@ -72,6 +93,24 @@ impl ZkSyncOp {
             ZkSyncOp::WithdrawNFT(_) => WithdrawNFTOp::CHUNKS,
         }
     }
+    /// Returns the size of the operation public data in bytes.
+    pub fn byte_size(&self) -> usize {
+        match self {
+            ZkSyncOp::Noop(_) => NoopOp::BYTE_SIZE,
+            ZkSyncOp::Deposit(_) => DepositOp::BYTE_SIZE,
+            ZkSyncOp::TransferToNew(_) => TransferToNewOp::BYTE_SIZE,
+            ZkSyncOp::Withdraw(_) => WithdrawOp::BYTE_SIZE,
+            ZkSyncOp::Close(_) => CloseOp::BYTE_SIZE,
+            ZkSyncOp::Transfer(_) => TransferOp::BYTE_SIZE,
+            ZkSyncOp::FullExit(_) => FullExitOp::BYTE_SIZE,
+            ZkSyncOp::ChangePubKeyOffchain(_) => ChangePubKeyOp::BYTE_SIZE,
+            ZkSyncOp::ForcedExit(_) => ForcedExitOp::BYTE_SIZE,
+            ZkSyncOp::Swap(_) => SwapOp::BYTE_SIZE,
+            ZkSyncOp::MintNFTOp(_) => MintNFTOp::BYTE_SIZE,
+            ZkSyncOp::WithdrawNFT(_) => WithdrawNFTOp::BYTE_SIZE,
+        }
+    }
+
     /// Get information about amounts in operation
     pub fn get_amount_info(&self) -> Option<Vec<(TokenId, BigUint)>> {
         match self {
@@ -97,6 +136,143 @@ impl ZkSyncOp {
         }
     }
 
+    /// Returns the fee paid for the operation, if any.
+    ///
+    /// Priority operations (`Deposit`, `FullExit`) as well as `Close` and `Noop`
+    /// do not carry a fee.
+    pub fn fee(&self) -> Option<BigUint> {
+        match self {
+            ZkSyncOp::Transfer(op) => Some(op.tx.fee.clone()),
+            ZkSyncOp::TransferToNew(op) => Some(op.tx.fee.clone()),
+            ZkSyncOp::Withdraw(op) => Some(op.tx.fee.clone()),
+            ZkSyncOp::WithdrawNFT(op) => Some(op.tx.fee.clone()),
+            ZkSyncOp::ChangePubKeyOffchain(op) => Some(op.tx.fee.clone()),
+            ZkSyncOp::ForcedExit(op) => Some(op.tx.fee.clone()),
+            ZkSyncOp::MintNFTOp(op) => Some(op.tx.fee.clone()),
+            ZkSyncOp::Swap(op) => Some(op.tx.fee.clone()),
+            ZkSyncOp::Deposit(_)
+            | ZkSyncOp::FullExit(_)
+            | ZkSyncOp::Close(_)
+            | ZkSyncOp::Noop(_) => None,
+        }
+    }
+
+    /// Returns the ID of the account paying the fee of the operation.
+    ///
+    /// Unlike `get_updated_account_ids`, only the account charged for the operation is returned,
+    /// e.g. the initiator of `ForcedExit` rather than its target.
+    /// Operations without fee (see `fee`) have no payer.
+    pub fn fee_payer(&self) -> Option<AccountId> {
+        match self {
+            ZkSyncOp::Transfer(op) => Some(op.from),
+            ZkSyncOp::TransferToNew(op) => Some(op.from),
+            ZkSyncOp::Withdraw(op) => Some(op.account_id),
+            ZkSyncOp::WithdrawNFT(op) => Some(op.tx.account_id),
+            ZkSyncOp::ChangePubKeyOffchain(op) => Some(op.account_id),
+            ZkSyncOp::ForcedExit(op) => Some(op.tx.initiator_account_id),
+            ZkSyncOp::MintNFTOp(op) => Some(op.creator_account_id),
+            ZkSyncOp::Swap(op) => Some(op.submitter),
+            ZkSyncOp::Deposit(_)
+            | ZkSyncOp::FullExit(_)
+            | ZkSyncOp::Close(_)
+            | ZkSyncOp::Noop(_) => None,
+        }
+    }
+
+    /// Returns the encoding of the amount in the operation public data, if the operation has an amount.
+    ///
+    /// Matches the `get_public_data` implementation of each operation: priority operations and
+    /// operations withdrawing funds to L1 store the full `u128` amount, while operations within
+    /// the rollup store the packed amount. Operations with NFTs carry no amount at all.
+    pub fn amount_encoding(&self) -> Option<AmountEncoding> {
+        match self {
+            ZkSyncOp::Deposit(_)
+            | ZkSyncOp::Withdraw(_)
+            | ZkSyncOp::FullExit(_)
+            | ZkSyncOp::ForcedExit(_) => Some(AmountEncoding::Raw128),
+            ZkSyncOp::Transfer(_) | ZkSyncOp::TransferToNew(_) | ZkSyncOp::Swap(_) => {
+                Some(AmountEncoding::PackedFloat)
+            }
+            ZkSyncOp::WithdrawNFT(_)
+            | ZkSyncOp::MintNFTOp(_)
+            | ZkSyncOp::ChangePubKeyOffchain(_)
+            | ZkSyncOp::Close(_)
+            | ZkSyncOp::Noop(_) => None,
+        }
+    }
+
+    /// Returns the Ethereum address receiving the funds withdrawn by the operation, if any.
+    ///
+    /// Operations that stay within the rollup have no recipient address.
+    pub fn recipient_address(&self) -> Option<Address> {
+        match self {
+            ZkSyncOp::Withdraw(op) => Some(op.tx.to),
+            ZkSyncOp::WithdrawNFT(op) => Some(op.tx.to),
+            ZkSyncOp::FullExit(op) => Some(op.priority_op.eth_address),
+            ZkSyncOp::ForcedExit(op) => Some(op.tx.target),
+            _ => None,
+        }
+    }
+
+    /// Returns a flat JSON description of the operation for external consumers.
+    ///
+    /// Amounts are represented as decimal strings and addresses as hex strings.
+    pub fn to_summary_json(&self) -> serde_json::Value {
+        // Type names are the same as the serialization tags of the stored operations.
+        let (op_type, op_code) = match self {
+            ZkSyncOp::Noop(_) => ("Noop", NoopOp::OP_CODE),
+            ZkSyncOp::Deposit(_) => ("Deposit", DepositOp::OP_CODE),
+            ZkSyncOp::TransferToNew(_) => ("TransferToNew", TransferToNewOp::OP_CODE),
+            ZkSyncOp::Withdraw(_) => ("Withdraw", WithdrawOp::OP_CODE),
+            ZkSyncOp::Close(_) => ("Close", CloseOp::OP_CODE),
+            ZkSyncOp::Transfer(_) => ("Transfer", TransferOp::OP_CODE),
+            ZkSyncOp::FullExit(_) => ("FullExit", FullExitOp::OP_CODE),
+            ZkSyncOp::ChangePubKeyOffchain(_) => ("ChangePubKeyOffchain", ChangePubKeyOp::OP_CODE),
+            ZkSyncOp::ForcedExit(_) => ("ForcedExit", ForcedExitOp::OP_CODE),
+            ZkSyncOp::Swap(_) => ("Swap", SwapOp::OP_CODE),
+            ZkSyncOp::MintNFTOp(_) => ("MintNFTOp", MintNFTOp::OP_CODE),
+            ZkSyncOp::WithdrawNFT(_) => ("WithdrawNFT", WithdrawNFTOp::OP_CODE),
+        };
+        let amounts = self
+            .get_amount_info()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(token, amount)| serde_json::json!({ "token": token, "amount": amount.to_string() }))
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "type": op_type,
+            "op_code": op_code,
+            "chunks": self.chunks(),
+            "accounts": self.get_updated_account_ids(),
+            "amounts": amounts,
+            "fee": self.fee().map(|fee| fee.to_string()),
+            "recipient_address": self.recipient_address().map(|address| format!("{:?}", address)),
+        })
+    }
+
+    /// Returns `true` if the operation moves funds from the rollup to Ethereum.
+    ///
+    /// `FullExit` and `ForcedExit` only do so if the withdrawal was successful.
+    pub fn is_withdrawal_to_l1(&self) -> bool {
+        self.l1_withdrawal().is_some()
+    }
+
+    /// Returns the token and the amount withdrawn to Ethereum by the operation, if any.
+    fn l1_withdrawal(&self) -> Option<(TokenId, BigUint)> {
+        match self {
+            ZkSyncOp::Withdraw(op) => Some((op.tx.token, op.tx.amount.clone())),
+            ZkSyncOp::FullExit(op) => op
+                .withdraw_amount()
+                .map(|amount| (op.priority_op.token, amount)),
+            ZkSyncOp::ForcedExit(op) => op
+                .withdraw_amount
+                .as_ref()
+                .map(|amount| (op.tx.token, amount.0.clone())),
+            _ => None,
+        }
+    }
+
     /// Returns the public data required for the Ethereum smart contract to commit the operation.
     pub fn public_data(&self) -> Vec<u8> {
         match self {
@@ -183,6 +359,165 @@ impl ZkSyncOp {
         }
     }
 
+    /// Attempts to restore the sequence of operations from the public data of the whole block.
+    ///
+    /// Each operation is sliced from the blob according to the length determined by its opcode.
+    pub fn parse_pubdata_blob(bytes: &[u8]) -> Result<Vec<Self>, PublicDataDecodeError> {
+        let mut ops = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let op_len = Self::public_data_length(bytes[offset])
+                .map_err(|_| PublicDataDecodeError::UnknownOperationType)?;
+            let op_bytes = bytes
+                .get(offset..offset + op_len)
+                .ok_or(PublicDataDecodeError::TruncatedData)?;
+            ops.push(Self::from_public_data(op_bytes)?);
+            offset += op_len;
+        }
+        Ok(ops)
+    }
+
+    /// Returns the CRC32 checksum of the operation public data.
+    ///
+    /// It may be stored alongside the operation to detect the corruption of the stored data
+    /// (see `verify_against_checksum`). It's not a cryptographic hash and must not be used
+    /// to protect the data from intentional modification.
+    pub fn pubdata_checksum(&self) -> u32 {
+        crc32fast::hash(&self.public_data())
+    }
+
+    /// Checks that the operation public data matches the checksum obtained with `pubdata_checksum`.
+    pub fn verify_against_checksum(&self, expected: u32) -> bool {
+        self.pubdata_checksum() == expected
+    }
+
+    /// Same as `parse_pubdata_blob`, but also checks that the decoded operations take exactly
+    /// `declared_chunks` chunks (e.g. the block size referenced by the commitment), so that
+    /// a truncated or extended blob is rejected.
+    pub fn validate_and_decode_pubdata(
+        bytes: &[u8],
+        declared_chunks: usize,
+    ) -> Result<Vec<Self>, PublicDataDecodeError> {
+        let ops = Self::parse_pubdata_blob(bytes)?;
+        let actual = ops.iter().map(Self::chunks).sum();
+        if actual != declared_chunks {
+            return Err(PublicDataDecodeError::ChunksCountMismatch {
+                declared: declared_chunks,
+                actual,
+            });
+        }
+        Ok(ops)
+    }
+
+    /// Reads the next operation from the public data stream.
+    ///
+    /// Returns `Ok(None)` if the stream has ended right at the operation boundary,
+    /// and an error if it ends in the middle of an operation.
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Option<Self>, PublicDataDecodeError> {
+        let into_decode_error = |err: io::Error| match err.kind() {
+            io::ErrorKind::UnexpectedEof => PublicDataDecodeError::TruncatedData,
+            _ => PublicDataDecodeError::ReadError(err.to_string()),
+        };
+
+        let mut op_type = [0u8; 1];
+        if let Err(err) = reader.read_exact(&mut op_type) {
+            return match err.kind() {
+                io::ErrorKind::UnexpectedEof => Ok(None),
+                _ => Err(into_decode_error(err)),
+            };
+        }
+
+        let op_len = Self::public_data_length(op_type[0])
+            .map_err(|_| PublicDataDecodeError::UnknownOperationType)?;
+        let mut bytes = vec![0u8; op_len];
+        bytes[0] = op_type[0];
+        reader
+            .read_exact(&mut bytes[1..])
+            .map_err(into_decode_error)?;
+
+        Self::from_public_data(&bytes).map(Some)
+    }
+
+    /// Returns the op code of the operation, i.e. the first byte of its public data.
+    pub fn op_code(&self) -> u8 {
+        match self {
+            ZkSyncOp::Noop(_) => NoopOp::OP_CODE,
+            ZkSyncOp::Deposit(_) => DepositOp::OP_CODE,
+            ZkSyncOp::TransferToNew(_) => TransferToNewOp::OP_CODE,
+            ZkSyncOp::Withdraw(_) => WithdrawOp::OP_CODE,
+            ZkSyncOp::Close(_) => CloseOp::OP_CODE,
+            ZkSyncOp::Transfer(_) => TransferOp::OP_CODE,
+            ZkSyncOp::FullExit(_) => FullExitOp::OP_CODE,
+            ZkSyncOp::ChangePubKeyOffchain(_) => ChangePubKeyOp::OP_CODE,
+            ZkSyncOp::ForcedExit(_) => ForcedExitOp::OP_CODE,
+            ZkSyncOp::Swap(_) => SwapOp::OP_CODE,
+            ZkSyncOp::MintNFTOp(_) => MintNFTOp::OP_CODE,
+            ZkSyncOp::WithdrawNFT(_) => WithdrawNFTOp::OP_CODE,
+        }
+    }
+
+    /// Writes the operation with all its fields (unlike the public data) as a self-describing frame:
+    /// the op code, the big-endian `u32` length of the encoded operation and the operation
+    /// encoded as JSON. Allows to store operations in a log that can be scanned without knowing
+    /// the operation sizes in advance.
+    ///
+    /// JSON is used instead of bincode since the operations are internally tagged enums and
+    /// the transactions have flattened fields, neither of which bincode can encode and decode.
+    ///
+    /// Fails without writing anything if the encoded operation exceeds `MAX_FRAMED_OP_SIZE`.
+    pub fn write_framed<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let body = serde_json::to_vec(self)?;
+        let too_large = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Encoded operation is too large: {} bytes", body.len()),
+            )
+        };
+        if body.len() > MAX_FRAMED_OP_SIZE {
+            return Err(too_large());
+        }
+        let len = u32::try_from(body.len()).map_err(|_| too_large())?;
+        writer.write_all(&[self.op_code()])?;
+        writer.write_all(&len.to_be_bytes())?;
+        writer.write_all(&body)
+    }
+
+    /// Reads the next operation written with `write_framed`.
+    ///
+    /// Returns `Ok(None)` if the stream has ended right at the frame boundary,
+    /// and an error if it ends in the middle of a frame. Frames declaring the length above
+    /// `MAX_FRAMED_OP_SIZE` are rejected before reading the operation.
+    pub fn read_framed<R: Read>(reader: &mut R) -> Result<Option<Self>, FramedOpDecodeError> {
+        let into_decode_error = |err: io::Error| match err.kind() {
+            io::ErrorKind::UnexpectedEof => FramedOpDecodeError::TruncatedData,
+            _ => FramedOpDecodeError::ReadError(err.to_string()),
+        };
+
+        let mut op_type = [0u8; 1];
+        if let Err(err) = reader.read_exact(&mut op_type) {
+            return match err.kind() {
+                io::ErrorKind::UnexpectedEof => Ok(None),
+                _ => Err(into_decode_error(err)),
+            };
+        }
+
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len).map_err(into_decode_error)?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAMED_OP_SIZE {
+            return Err(FramedOpDecodeError::FrameTooLarge(len));
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).map_err(into_decode_error)?;
+
+        let op: Self = serde_json::from_slice(&body)
+            .map_err(|err| FramedOpDecodeError::InvalidOperation(err.to_string()))?;
+        if op.op_code() != op_type[0] {
+            return Err(FramedOpDecodeError::OpCodeMismatch);
+        }
+        Ok(Some(op))
+    }
+
     /// Attempts to restore the operation from the public data committed on the Ethereum smart contract
     /// prior to v6 upgrade. The token id bit width is 2 bytes instead of 4.
     ///
@@ -239,6 +574,125 @@ impl ZkSyncOp {
         .map(|chunks| chunks * CHUNK_BYTES)
     }
 
+    /// Returns the layout of the public data for a certain type of operation as
+    /// `(field_name, offset, length)` tuples, with offsets and lengths in bytes.
+    ///
+    /// The first field is always the op code. The zero padding up to the end of the last
+    /// chunk is not listed.
+    pub fn field_layout(
+        op_type: u8,
+    ) -> Result<Vec<(String, usize, usize)>, UnexpectedOperationType> {
+        const ACCOUNT_ID: usize = ACCOUNT_ID_BIT_WIDTH / 8;
+        const TOKEN: usize = TOKEN_BIT_WIDTH / 8;
+        const BALANCE: usize = BALANCE_BIT_WIDTH / 8;
+        const PACKED_AMOUNT: usize = (AMOUNT_EXPONENT_BIT_WIDTH + AMOUNT_MANTISSA_BIT_WIDTH) / 8;
+        const PACKED_FEE: usize = (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8;
+        const ETH_ADDRESS: usize = ETH_ADDRESS_BIT_WIDTH / 8;
+        const ADDRESS: usize = ADDRESS_WIDTH / 8;
+        const SERIAL_ID: usize = SERIAL_ID_WIDTH / 8;
+        const CONTENT_HASH: usize = CONTENT_HASH_WIDTH / 8;
+
+        let fields: &[(&str, usize)] = match op_type {
+            NoopOp::OP_CODE => &[],
+            DepositOp::OP_CODE => &[
+                ("account_id", ACCOUNT_ID),
+                ("token", TOKEN),
+                ("amount", BALANCE),
+                ("address", FR_ADDRESS_LEN),
+            ],
+            TransferToNewOp::OP_CODE => &[
+                ("from_account_id", ACCOUNT_ID),
+                ("token", TOKEN),
+                ("amount", PACKED_AMOUNT),
+                ("to_address", FR_ADDRESS_LEN),
+                ("to_account_id", ACCOUNT_ID),
+                ("fee", PACKED_FEE),
+            ],
+            WithdrawOp::OP_CODE => &[
+                ("account_id", ACCOUNT_ID),
+                ("token", TOKEN),
+                ("amount", BALANCE),
+                ("fee", PACKED_FEE),
+                ("eth_address", ETH_ADDRESS),
+            ],
+            CloseOp::OP_CODE => &[("account_id", ACCOUNT_ID)],
+            TransferOp::OP_CODE => &[
+                ("from_account_id", ACCOUNT_ID),
+                ("token", TOKEN),
+                ("to_account_id", ACCOUNT_ID),
+                ("amount", PACKED_AMOUNT),
+                ("fee", PACKED_FEE),
+            ],
+            FullExitOp::OP_CODE => &[
+                ("account_id", ACCOUNT_ID),
+                ("eth_address", ETH_ADDRESS),
+                ("token", TOKEN),
+                ("amount", BALANCE),
+                ("creator_account_id", ACCOUNT_ID),
+                ("creator_address", ADDRESS),
+                ("serial_id", SERIAL_ID),
+                ("content_hash", CONTENT_HASH),
+            ],
+            ChangePubKeyOp::OP_CODE => &[
+                ("account_id", ACCOUNT_ID),
+                ("new_pubkey_hash", NEW_PUBKEY_HASH_WIDTH / 8),
+                ("address", ADDRESS),
+                ("nonce", NONCE_BIT_WIDTH / 8),
+                ("fee_token", TOKEN),
+                ("fee", PACKED_FEE),
+            ],
+            ForcedExitOp::OP_CODE => &[
+                ("initiator_account_id", ACCOUNT_ID),
+                ("target_account_id", ACCOUNT_ID),
+                ("token", TOKEN),
+                ("amount", BALANCE),
+                ("fee", PACKED_FEE),
+                ("target_address", ETH_ADDRESS),
+            ],
+            SwapOp::OP_CODE => &[
+                ("account_id_0", ACCOUNT_ID),
+                ("recipient_id_0", ACCOUNT_ID),
+                ("account_id_1", ACCOUNT_ID),
+                ("recipient_id_1", ACCOUNT_ID),
+                ("submitter_id", ACCOUNT_ID),
+                ("token_0", TOKEN),
+                ("token_1", TOKEN),
+                ("fee_token", TOKEN),
+                ("amount_0", PACKED_AMOUNT),
+                ("amount_1", PACKED_AMOUNT),
+                ("fee", PACKED_FEE),
+                ("nonce_mask", 1),
+            ],
+            MintNFTOp::OP_CODE => &[
+                ("creator_account_id", ACCOUNT_ID),
+                ("recipient_account_id", ACCOUNT_ID),
+                ("content_hash", CONTENT_HASH),
+                ("fee_token", TOKEN),
+                ("fee", PACKED_FEE),
+            ],
+            WithdrawNFTOp::OP_CODE => &[
+                ("account_id", ACCOUNT_ID),
+                ("creator_account_id", ACCOUNT_ID),
+                ("creator_address", ADDRESS),
+                ("serial_id", SERIAL_ID),
+                ("content_hash", CONTENT_HASH),
+                ("to_address", ETH_ADDRESS),
+                ("token", TOKEN),
+                ("fee_token", TOKEN),
+                ("fee", PACKED_FEE),
+            ],
+            _ => return Err(UnexpectedOperationType()),
+        };
+
+        let mut layout = vec![("op_code".to_string(), 0, 1)];
+        let mut offset = 1;
+        for &(name, length) in fields {
+            layout.push((name.to_string(), offset, length));
+            offset += length;
+        }
+        Ok(layout)
+    }
+
     /// Returns the expected number of chunks for a certain type of operation
     /// prior to v6 upgrade.
     pub fn legacy_public_data_length(op_type: u8) -> Result<usize, UnexpectedOperationType> {
@@ -258,12 +712,20 @@ impl ZkSyncOp {
     }
 
     /// Attempts to interpret the operation as the L2 transaction.
+    ///
+    /// The transaction is only complete if the operation was created from it. For the operations
+    /// restored from the public data (see `from_public_data`) the fields that the public data
+    /// lacks, such as the signature, nonce and addresses, are set to defaults: the returned
+    /// transaction has a different hash than the original one and won't pass the signature
+    /// verification. Use `is_fully_reconstructable_from_pubdata` to tell such operations apart.
+    ///
+    /// `Close` operations are disabled and can only be obtained from the public data, so
+    /// the restored transaction would always be non-functional and an error is returned for them.
     pub fn try_get_tx(&self) -> Result<ZkSyncTx, UnexpectedOperationType> {
         match self {
             ZkSyncOp::Transfer(op) => Ok(ZkSyncTx::Transfer(Box::new(op.tx.clone()))),
             ZkSyncOp::TransferToNew(op) => Ok(ZkSyncTx::Transfer(Box::new(op.tx.clone()))),
             ZkSyncOp::Withdraw(op) => Ok(ZkSyncTx::Withdraw(Box::new(op.tx.clone()))),
-            ZkSyncOp::Close(op) => Ok(ZkSyncTx::Close(Box::new(op.tx.clone()))),
             ZkSyncOp::ChangePubKeyOffchain(op) => {
                 Ok(ZkSyncTx::ChangePubKey(Box::new(op.tx.clone())))
             }
@@ -275,6 +737,60 @@ impl ZkSyncOp {
         }
     }
 
+    /// Creates the operation executing the L2 transaction, the inverse of `try_get_tx`.
+    ///
+    /// `from` is the ID of the account initiating the transaction, `to` is the ID of the recipient
+    /// account, required for transfers and NFT mints. `to_is_new` tells whether the recipient
+    /// account doesn't exist yet, which turns a transfer into `TransferToNew`.
+    ///
+    /// Transactions whose operations need more data than account IDs (e.g. the withdrawn amount
+    /// of `ForcedExit`) are not supported.
+    pub fn from_tx(
+        tx: &ZkSyncTx,
+        from: AccountId,
+        to: Option<AccountId>,
+        to_is_new: bool,
+    ) -> Result<Self, OpFromTxError> {
+        let op = match tx {
+            ZkSyncTx::Transfer(tx) => {
+                let to = to.ok_or(OpFromTxError::MissingRecipient)?;
+                if to_is_new {
+                    TransferToNewOp {
+                        tx: *tx.clone(),
+                        from,
+                        to,
+                    }
+                    .into()
+                } else {
+                    TransferOp {
+                        tx: *tx.clone(),
+                        from,
+                        to,
+                    }
+                    .into()
+                }
+            }
+            ZkSyncTx::Withdraw(tx) => WithdrawOp {
+                tx: *tx.clone(),
+                account_id: from,
+            }
+            .into(),
+            ZkSyncTx::ChangePubKey(tx) => ChangePubKeyOp {
+                tx: *tx.clone(),
+                account_id: from,
+            }
+            .into(),
+            ZkSyncTx::MintNFT(tx) => MintNFTOp {
+                tx: *tx.clone(),
+                creator_account_id: from,
+                recipient_account_id: to.ok_or(OpFromTxError::MissingRecipient)?,
+            }
+            .into(),
+            _ => return Err(OpFromTxError::UnsupportedTxType),
+        };
+        Ok(op)
+    }
+
     /// Attempts to interpret the operation as the L1 priority operation.
     pub fn try_get_priority_op(&self) -> Result<ZkSyncPriorityOp, UnexpectedOperationType> {
         match self {
@@ -284,6 +800,19 @@ impl ZkSyncOp {
         }
     }
 
+    /// Returns the L1 priority operation along with the ID of the account it was applied to,
+    /// which are needed to match the operation against the priority queue.
+    /// Returns `None` for operations not originating from the priority queue.
+    pub fn priority_op_bundle(&self) -> Option<(ZkSyncPriorityOp, AccountId)> {
+        let account_id = match self {
+            ZkSyncOp::Deposit(op) => op.account_id,
+            ZkSyncOp::FullExit(op) => op.priority_op.account_id,
+            _ => return None,
+        };
+        let priority_op = self.try_get_priority_op().ok()?;
+        Some((priority_op, account_id))
+    }
+
     /// Returns the list of account IDs affected by this operation.
     pub fn get_updated_account_ids(&self) -> Vec<AccountId> {
         match self {
@@ -324,11 +853,260 @@ impl ZkSyncOp {
         )
     }
 
+    /// Returns `true` if the operation restored from the public data (see `from_public_data`)
+    /// has all the data affecting the L2 state.
+    ///
+    /// This is the case for priority operations, which only lack L1 details like the deposit sender.
+    /// Operations created from L2 transactions can't be fully restored: the public data never
+    /// contains signatures, so the restored transactions have default ones and won't pass
+    /// the signature verification. Nonces and addresses of the accounts are missing as well.
+    pub fn is_fully_reconstructable_from_pubdata(&self) -> bool {
+        matches!(
+            self,
+            ZkSyncOp::Noop(_) | ZkSyncOp::Deposit(_) | ZkSyncOp::FullExit(_)
+        )
+    }
+
+    /// Returns `true` if the operation always adds a new account to the tree, so the state
+    /// reconstruction has to allocate a new leaf for its recipient.
+    ///
+    /// Deposits create the recipient account only if it doesn't exist yet, which depends
+    /// on the state rather than on the operation itself, so `false` is returned for them.
+    pub fn is_account_creating(&self) -> bool {
+        matches!(self, ZkSyncOp::TransferToNew(_))
+    }
+
     pub fn is_priority_op(&self) -> bool {
         matches!(self, &ZkSyncOp::Deposit(_) | &ZkSyncOp::FullExit(_))
     }
 }
 
+/// Encoding of the token amount in the operation public data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountEncoding {
+    /// Big-endian `u128` taking `BALANCE_BIT_WIDTH` bits.
+    Raw128,
+    /// Floating-point number packed with `pack_token_amount`.
+    PackedFloat,
+}
+
+/// Record of the withdrawal data sent to the Ethereum smart contract (see `ZkSyncOp::withdrawal_data`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithdrawalRecord {
+    /// Whether the withdrawal is added to the pending withdrawals queue
+    /// (the `addToPendingWithdrawalsQueue` flag of the smart contract).
+    pub add_to_queue: bool,
+    pub to: Address,
+    pub token: TokenId,
+    pub amount: BigUint,
+}
+
+impl WithdrawalRecord {
+    /// Decodes the withdrawal data record of `Withdraw`, `ForcedExit` or `FullExit` operation.
+    ///
+    /// Returns `None` if the bytes are not such a record. Records of `WithdrawNFT` operations
+    /// carry no amount and are not supported.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let token_offset = 1 + FR_ADDRESS_LEN;
+        let amount_offset = token_offset + TOKEN_BIT_WIDTH / 8;
+        let amount_end = amount_offset + BALANCE_BIT_WIDTH / 8;
+        // `FullExit` records additionally contain the NFT creator account id.
+        if bytes.len() != amount_end && bytes.len() != amount_end + ACCOUNT_ID_BIT_WIDTH / 8 {
+            return None;
+        }
+
+        let add_to_queue = match bytes[0] {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        let to = Address::from_slice(&bytes[1..token_offset]);
+        let token = u32::from_bytes(&bytes[token_offset..amount_offset])?;
+        let amount = u128::from_bytes(&bytes[amount_offset..amount_end])?;
+
+        Some(Self {
+            add_to_queue,
+            to,
+            token: TokenId(token),
+            amount: BigUint::from(amount),
+        })
+    }
+}
+
+/// Returns `true` if the account is reserved by the protocol and isn't controlled by any user.
+///
+/// The only such account is the NFT storage account. It has no signing key, so it can't initiate
+/// transactions, and it can't be the target of a forced exit. Deposits to it are still accepted,
+/// since priority operations are sent from L1 without such checks.
+///
+/// Note that the account 0 is not reserved: it's a regular account of the operator collecting
+/// the fees, which may receive deposits and withdraw funds as any other account.
+pub fn is_reserved_account(id: AccountId) -> bool {
+    id == NFT_STORAGE_ACCOUNT_ID
+}
+
+/// Returns the state of the primary account affected by the operation (the deposit recipient,
+/// the sender of a transfer or a withdrawal, or the fully exited account) after applying
+/// the operation to its `prior` state, i.e. the preimage of the updated account tree leaf.
+///
+/// Returns `None` for other operations and if the account balance is insufficient.
+pub fn resulting_account(op: &ZkSyncOp, prior: &Account) -> Option<Account> {
+    fn debit(account: &mut Account, token: TokenId, amount: &BigUint) -> Option<()> {
+        if account.get_balance(token) < *amount {
+            return None;
+        }
+        account.sub_balance(token, amount);
+        Some(())
+    }
+
+    let mut account = prior.clone();
+    match op {
+        ZkSyncOp::Deposit(op) => {
+            if account.is_default() {
+                account.address = op.priority_op.to;
+            }
+            account.add_balance(op.priority_op.token, &op.priority_op.amount);
+        }
+        ZkSyncOp::Transfer(op) => {
+            debit(&mut account, op.tx.token, &(&op.tx.amount + &op.tx.fee))?;
+            *account.nonce += 1;
+        }
+        ZkSyncOp::TransferToNew(op) => {
+            debit(&mut account, op.tx.token, &(&op.tx.amount + &op.tx.fee))?;
+            *account.nonce += 1;
+        }
+        ZkSyncOp::Withdraw(op) => {
+            debit(&mut account, op.tx.token, &(&op.tx.amount + &op.tx.fee))?;
+            *account.nonce += 1;
+        }
+        ZkSyncOp::FullExit(op) => {
+            if let Some(amount) = &op.withdraw_amount {
+                debit(&mut account, op.priority_op.token, &amount.0)?;
+            }
+        }
+        _ => return None,
+    }
+    Some(account)
+}
+
+/// Checks that all the bytes of the operation public data between the end of its fields
+/// (`data_end`) and the end of its last chunk (`total`) are zero.
+///
+/// Decoders must call it after reading the operation fields: otherwise different byte blobs
+/// would be decoded into the same operation, which breaks the injectivity of the commitment.
+pub(crate) fn ensure_zero_padding(bytes: &[u8], data_end: usize, total: usize) -> bool {
+    bytes
+        .get(data_end..total)
+        .map_or(false, |padding| padding.iter().all(|&byte| byte == 0))
+}
+
+/// Number of operations of each type in the block.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpHistogram {
+    /// Number of operations by op code, excluding noops.
+    pub ops: BTreeMap<u8, usize>,
+    /// Number of noop operations filling the unused block capacity.
+    pub noops: usize,
+}
+
+impl OpHistogram {
+    /// Builds the histogram from the public data of the whole block
+    /// (see `ZkSyncOp::parse_pubdata_blob`).
+    pub fn from_public_data(bytes: &[u8]) -> Result<Self, PublicDataDecodeError> {
+        let mut histogram = Self::default();
+        for op in ZkSyncOp::parse_pubdata_blob(bytes)? {
+            match op {
+                ZkSyncOp::Noop(_) => histogram.noops += 1,
+                op => *histogram.ops.entry(op.op_code()).or_default() += 1,
+            }
+        }
+        Ok(histogram)
+    }
+}
+
+/// Returns the set of tokens moved by the value-bearing operations of the block.
+pub fn tokens_in_block(ops: &[ZkSyncOp]) -> BTreeSet<TokenId> {
+    ops.iter()
+        .filter_map(ZkSyncOp::get_amount_info)
+        .flatten()
+        .map(|(token, _)| token)
+        .collect()
+}
+
+/// Returns the total amount of each token withdrawn to Ethereum by the operations of the block.
+///
+/// Failed exits (i.e. ones without the withdrawn amount) are not taken into account.
+pub fn l1_outflow(ops: &[ZkSyncOp]) -> HashMap<TokenId, BigUint> {
+    let mut outflow = HashMap::new();
+    for (token, amount) in ops.iter().filter_map(ZkSyncOp::l1_withdrawal) {
+        *outflow.entry(token).or_insert_with(BigUint::default) += amount;
+    }
+    outflow
+}
+
+/// Returns the total amount of each token in the withdrawal data records
+/// (e.g. the whole pending withdrawals queue being reconciled).
+///
+/// Amounts are accumulated as `BigUint`, since the sum of many `u128` amounts may not fit into `u128`.
+pub fn withdrawal_totals<'a>(
+    records: impl IntoIterator<Item = &'a WithdrawalRecord>,
+) -> HashMap<TokenId, BigUint> {
+    let mut totals = HashMap::new();
+    for record in records {
+        *totals.entry(record.token).or_insert_with(BigUint::default) += &record.amount;
+    }
+    totals
+}
+
+/// The first difference found between two sequences of operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpDiff {
+    /// Position of the differing operation in the sequences.
+    pub index: usize,
+    /// Human-readable description of the difference.
+    pub reason: String,
+}
+
+/// Compares two sequences of operations by their public data (i.e. the way they are
+/// represented on-chain) and returns the first difference, if any.
+pub fn diff_op_sequences(a: &[ZkSyncOp], b: &[ZkSyncOp]) -> Option<OpDiff> {
+    for (index, (op_a, op_b)) in a.iter().zip(b).enumerate() {
+        let (pubdata_a, pubdata_b) = (op_a.public_data(), op_b.public_data());
+        if pubdata_a == pubdata_b {
+            continue;
+        }
+
+        let reason = if pubdata_a[0] != pubdata_b[0] {
+            format!(
+                "Operation type differs: {:#04x} vs {:#04x}",
+                pubdata_a[0], pubdata_b[0]
+            )
+        } else if pubdata_a.len() != pubdata_b.len() {
+            format!(
+                "Public data length differs: {} vs {}",
+                pubdata_a.len(),
+                pubdata_b.len()
+            )
+        } else {
+            let offset = pubdata_a
+                .iter()
+                .zip(&pubdata_b)
+                .position(|(byte_a, byte_b)| byte_a != byte_b)
+                .expect("Public data differs");
+            format!("Public data differs at byte {}", offset)
+        };
+        return Some(OpDiff { index, reason });
+    }
+
+    if a.len() != b.len() {
+        return Some(OpDiff {
+            index: a.len().min(b.len()),
+            reason: format!("Number of operations differs: {} vs {}", a.len(), b.len()),
+        });
+    }
+    None
+}
+
 impl From<NoopOp> for ZkSyncOp {
     fn from(op: NoopOp) -> Self {
         Self::Noop(op)
@@ -4,13 +4,18 @@ use zksync_basic_types::AccountId;
 use zksync_crypto::params::{CHUNK_BYTES, LEGACY_CHUNK_BYTES};
 
 /// Noop operation. For details, see the documentation of [`ZkSyncOp`](./operations/enum.ZkSyncOp.html).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NoopOp {}
 
 impl NoopOp {
     pub const CHUNKS: usize = 1;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x00;
 
+    pub fn new() -> Self {
+        Self {}
+    }
+
     pub fn from_public_data(bytes: &[u8]) -> Result<Self, NoopOpError> {
         Self::parse_pub_data::<CHUNK_BYTES>(bytes)
     }
@@ -27,7 +32,7 @@ impl NoopOp {
     }
 
     pub(crate) fn get_public_data(&self) -> Vec<u8> {
-        vec![0; Self::CHUNKS * CHUNK_BYTES]
+        vec![0; Self::BYTE_SIZE]
     }
 
     pub(crate) fn get_updated_account_ids(&self) -> Vec<AccountId> {
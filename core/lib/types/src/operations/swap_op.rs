@@ -8,7 +8,7 @@ use zksync_crypto::primitives::FromBytes;
 
 use crate::{
     helpers::{pack_fee_amount, pack_token_amount, unpack_fee_amount, unpack_token_amount},
-    operations::error::SwapOpError,
+    operations::{ensure_zero_padding, error::SwapOpError},
     tx::Order,
     AccountId, Address, Nonce, Swap, TokenId,
 };
@@ -24,6 +24,7 @@ pub struct SwapOp {
 
 impl SwapOp {
     pub const CHUNKS: usize = 5;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x0b;
 
     pub(crate) fn get_public_data(&self) -> Vec<u8> {
@@ -42,12 +43,12 @@ impl SwapOp {
         let nonce_mask = (!self.tx.orders.0.amount.is_zero() as u8)
             + (!self.tx.orders.1.amount.is_zero() as u8) * 2;
         data.push(nonce_mask);
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
     pub fn from_public_data(bytes: &[u8]) -> Result<Self, SwapOpError> {
-        if bytes.len() != Self::CHUNKS * CHUNK_BYTES {
+        if bytes.len() != Self::BYTE_SIZE {
             return Err(SwapOpError::PubdataSizeMismatch);
         }
 
@@ -94,6 +95,9 @@ impl SwapOp {
         let amount_1 = read_amount(amounts_offset + AMOUNT_BIT_WIDTH / 8)?;
         let nonce = Nonce(0); // It is unknown from pubdata
         let nonce_mask = bytes[fee_offset + FEE_BIT_WIDTH / 8];
+        if !ensure_zero_padding(bytes, fee_offset + FEE_BIT_WIDTH / 8 + 1, bytes.len()) {
+            return Err(SwapOpError::NonZeroPadding);
+        }
 
         let order_a = Order {
             account_id: account_id_0,
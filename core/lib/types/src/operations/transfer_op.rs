@@ -1,6 +1,9 @@
 use crate::{
-    helpers::{pack_fee_amount, pack_token_amount, unpack_fee_amount, unpack_token_amount},
-    operations::error::TransferOpError,
+    helpers::{
+        is_fee_amount_packable, is_token_amount_packable, pack_fee_amount, pack_token_amount,
+        unpack_fee_amount, unpack_token_amount,
+    },
+    operations::{ensure_zero_padding, error::TransferOpError, is_reserved_account},
     AccountId, Address, Nonce, TokenId, Transfer,
 };
 use serde::{Deserialize, Serialize};
@@ -23,8 +26,26 @@ pub struct TransferOp {
 
 impl TransferOp {
     pub const CHUNKS: usize = 2;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x05;
 
+    /// Creates a new operation, checking that both amount and fee of the transaction
+    /// can be packed into the public data without precision loss.
+    pub fn new_validated(
+        tx: Transfer,
+        from: AccountId,
+        to: AccountId,
+    ) -> Result<Self, TransferOpError> {
+        if !is_token_amount_packable(&tx.amount) {
+            return Err(TransferOpError::AmountNotPackable);
+        }
+        if !is_fee_amount_packable(&tx.fee) {
+            return Err(TransferOpError::FeeNotPackable);
+        }
+
+        Ok(Self { tx, from, to })
+    }
+
     pub(crate) fn get_public_data(&self) -> Vec<u8> {
         let mut data = vec![Self::OP_CODE];
         data.extend_from_slice(&self.from.to_be_bytes());
@@ -32,7 +53,7 @@ impl TransferOp {
         data.extend_from_slice(&self.to.to_be_bytes());
         data.extend_from_slice(&pack_token_amount(&self.tx.amount));
         data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
@@ -73,9 +94,19 @@ impl TransferOp {
             &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
         )
         .ok_or(TransferOpError::CannotGetFee)?;
+        if !ensure_zero_padding(
+            bytes,
+            fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8,
+            bytes.len(),
+        ) {
+            return Err(TransferOpError::NonZeroPadding);
+        }
         let nonce = 0; // It is unknown from pubdata
         let from_id = u32::from_bytes(&bytes[from_offset..from_offset + ACCOUNT_ID_BIT_WIDTH / 8])
             .ok_or(TransferOpError::CannotGetFromAccountId)?;
+        if is_reserved_account(AccountId(from_id)) {
+            return Err(TransferOpError::ReservedAccount);
+        }
         let to_id = u32::from_bytes(&bytes[to_offset..to_offset + ACCOUNT_ID_BIT_WIDTH / 8])
             .ok_or(TransferOpError::CannotGetToAccountId)?;
         let time_range = Default::default();
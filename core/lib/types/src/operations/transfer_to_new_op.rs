@@ -1,6 +1,6 @@
 use crate::{
     helpers::{pack_fee_amount, pack_token_amount, unpack_fee_amount, unpack_token_amount},
-    operations::error::TransferOpError,
+    operations::{ensure_zero_padding, error::TransferOpError, is_reserved_account},
     AccountId, Address, Nonce, TokenId, Transfer,
 };
 use serde::{Deserialize, Serialize};
@@ -23,6 +23,7 @@ pub struct TransferToNewOp {
 
 impl TransferToNewOp {
     pub const CHUNKS: usize = 6;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x02;
 
     pub(crate) fn get_public_data(&self) -> Vec<u8> {
@@ -33,7 +34,7 @@ impl TransferToNewOp {
         data.extend_from_slice(self.tx.to.as_bytes());
         data.extend_from_slice(&self.to.to_be_bytes());
         data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
@@ -64,6 +65,9 @@ impl TransferToNewOp {
 
         let from_id = u32::from_bytes(&bytes[from_offset..from_offset + ACCOUNT_ID_BIT_WIDTH / 8])
             .ok_or(TransferOpError::CannotGetFromAccountId)?;
+        if is_reserved_account(AccountId(from_id)) {
+            return Err(TransferOpError::ReservedAccount);
+        }
         let to_id = u32::from_bytes(&bytes[to_id_offset..to_id_offset + ACCOUNT_ID_BIT_WIDTH / 8])
             .ok_or(TransferOpError::CannotGetToAccountId)?;
         let from = Address::zero(); // It is unknown from pubdata;
@@ -79,6 +83,13 @@ impl TransferToNewOp {
             &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
         )
         .ok_or(TransferOpError::CannotGetFee)?;
+        if !ensure_zero_padding(
+            bytes,
+            fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8,
+            bytes.len(),
+        ) {
+            return Err(TransferOpError::NonZeroPadding);
+        }
         let nonce = 0; // It is unknown from pubdata
         let time_range = Default::default();
 
@@ -5,7 +5,7 @@ use zksync_crypto::params::{
 };
 use zksync_crypto::primitives::FromBytes;
 
-use crate::operations::error::WithdrawNFTOpError;
+use crate::operations::{ensure_zero_padding, error::WithdrawNFTOpError};
 use crate::{
     helpers::{pack_fee_amount, unpack_fee_amount},
     tx::WithdrawNFT,
@@ -24,6 +24,7 @@ pub struct WithdrawNFTOp {
 
 impl WithdrawNFTOp {
     pub const CHUNKS: usize = 10;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x0a;
     pub const WITHDRAW_DATA_PREFIX: [u8; 1] = [1];
 
@@ -38,7 +39,7 @@ impl WithdrawNFTOp {
         data.extend_from_slice(&self.tx.token.to_be_bytes());
         data.extend_from_slice(&self.tx.fee_token.to_be_bytes());
         data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
@@ -51,7 +52,7 @@ impl WithdrawNFTOp {
     }
 
     pub fn from_public_data(bytes: &[u8]) -> Result<Self, WithdrawNFTOpError> {
-        if bytes.len() != Self::CHUNKS * CHUNK_BYTES {
+        if bytes.len() != Self::BYTE_SIZE {
             return Err(WithdrawNFTOpError::PubdataSizeMismatch);
         }
 
@@ -88,6 +89,13 @@ impl WithdrawNFTOp {
             &bytes[fee_offset..fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8],
         )
         .ok_or(WithdrawNFTOpError::CannotGetFee)?;
+        if !ensure_zero_padding(
+            bytes,
+            fee_offset + (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8,
+            bytes.len(),
+        ) {
+            return Err(WithdrawNFTOpError::NonZeroPadding);
+        }
         let nonce = 0; // From pubdata it is unknown
         let time_range = Default::default();
 
@@ -1,6 +1,6 @@
 use crate::{
     helpers::{pack_fee_amount, unpack_fee_amount},
-    operations::error::WithdrawOpError,
+    operations::{ensure_zero_padding, error::WithdrawOpError, is_reserved_account},
     AccountId, Address, Nonce, TokenId, Withdraw,
 };
 use num::{BigUint, FromPrimitive, ToPrimitive};
@@ -23,6 +23,7 @@ pub struct WithdrawOp {
 
 impl WithdrawOp {
     pub const CHUNKS: usize = 6;
+    pub const BYTE_SIZE: usize = Self::CHUNKS * CHUNK_BYTES;
     pub const OP_CODE: u8 = 0x03;
     pub const WITHDRAW_DATA_PREFIX: [u8; 1] = [1];
 
@@ -33,7 +34,7 @@ impl WithdrawOp {
         data.extend_from_slice(&self.tx.amount.to_u128().unwrap().to_be_bytes());
         data.extend_from_slice(&pack_fee_amount(&self.tx.fee));
         data.extend_from_slice(self.tx.to.as_bytes());
-        data.resize(Self::CHUNKS * CHUNK_BYTES, 0x00);
+        data.resize(Self::BYTE_SIZE, 0x00);
         data
     }
 
@@ -72,12 +73,22 @@ impl WithdrawOp {
         let account_id =
             u32::from_bytes(&bytes[account_offset..account_offset + ACCOUNT_ID_BIT_WIDTH / 8])
                 .ok_or(WithdrawOpError::CannotGetAccountId)?;
+        if is_reserved_account(AccountId(account_id)) {
+            return Err(WithdrawOpError::ReservedAccount);
+        }
         let from = Address::zero(); // From pubdata it is unknown
         let token = u32::from_bytes(&bytes[token_id_offset..token_id_offset + token_bit_width / 8])
             .ok_or(WithdrawOpError::CannotGetTokenId)?;
         let to = Address::from_slice(
             &bytes[eth_address_offset..eth_address_offset + ETH_ADDRESS_BIT_WIDTH / 8],
         );
+        if !ensure_zero_padding(
+            bytes,
+            eth_address_offset + ETH_ADDRESS_BIT_WIDTH / 8,
+            bytes.len(),
+        ) {
+            return Err(WithdrawOpError::NonZeroPadding);
+        }
         let amount = BigUint::from_u128(
             u128::from_bytes(&bytes[amount_offset..amount_offset + BALANCE_BIT_WIDTH / 8])
                 .ok_or(WithdrawOpError::CannotGetAmount)?,
@@ -27,8 +27,14 @@ impl ProverJobStatus {
     }
 }
 
-pub const SINGLE_PROOF_JOB_PRIORITY: i32 = 1;
+// Jobs with lower priority values are taken from the queue first.
+pub const SINGLE_PROOF_JOB_PRIORITY: i32 = 2;
 pub const AGGREGATED_PROOF_JOB_PRIORITY: i32 = 0;
+/// Priority of the single proof jobs for blocks that withdraw funds to Ethereum.
+/// Users are waiting for these blocks to be finalized, so they're proven ahead of the routine ones,
+/// but still after the aggregated proofs that finalize the already proven blocks.
+/// Jobs with the same priority are still taken in the order they were added, i.e. by block number.
+pub const WITHDRAWAL_BLOCK_PROOF_JOB_PRIORITY: i32 = 1;
 
 #[derive(Debug, Clone)]
 pub struct ProverJob {
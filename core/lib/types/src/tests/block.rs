@@ -3,7 +3,12 @@ use zksync_crypto::ff::Field;
 use zksync_crypto::Fr;
 
 use super::utils::*;
-use crate::block::Block;
+use crate::block::{
+    verify_pubdata_against_commitment, Block, BlockChunksOverflow, CommitmentBuilder,
+    ExecutedOperations,
+};
+use crate::tx::TimeRange;
+use crate::ZkSyncTx;
 
 /// Checks that we cannot create a block with invalid block sizes provided.
 #[test]
@@ -84,6 +89,143 @@ fn test_get_eth_public_data() {
     assert!(block.get_eth_public_data().iter().all(|&i| i == 0));
 }
 
+#[test]
+fn test_verify_pubdata_against_commitment() {
+    let previous_block_root_hash = H256::repeat_byte(0x11);
+    let block = Block::new_from_available_block_sizes(
+        BlockNumber(1),
+        Fr::one(),
+        AccountId(0),
+        vec![create_change_pubkey_tx(), create_withdraw_tx()],
+        (0, 0),
+        &[100],
+        1_000_000.into(),
+        1_500_000.into(),
+        previous_block_root_hash,
+        0,
+    );
+    let public_data = block.get_eth_public_data();
+    let verify = |previous_block_root_hash, public_data: &[u8]| {
+        verify_pubdata_against_commitment(
+            block.block_number,
+            block.fee_account,
+            previous_block_root_hash,
+            block.new_root_hash,
+            block.timestamp,
+            public_data,
+            &block.block_commitment,
+        )
+    };
+
+    assert!(verify(previous_block_root_hash, &public_data));
+
+    let mut corrupted_public_data = public_data.clone();
+    corrupted_public_data[1] ^= 0x01;
+    assert!(!verify(previous_block_root_hash, &corrupted_public_data));
+    assert!(!verify(H256::default(), &public_data));
+
+    // Public data that cannot be decoded is rejected as well.
+    let mut undecodable_public_data = public_data.clone();
+    undecodable_public_data[0] = 0xff;
+    assert!(!verify(previous_block_root_hash, &undecodable_public_data));
+    assert!(!verify(
+        previous_block_root_hash,
+        &public_data[..public_data.len() - 1]
+    ));
+}
+
+#[test]
+fn test_commitment_builder() {
+    let previous_block_root_hash = H256::repeat_byte(0x11);
+    let block = Block::new_from_available_block_sizes(
+        BlockNumber(3),
+        Fr::one(),
+        AccountId(5),
+        vec![
+            create_change_pubkey_tx(),
+            create_full_exit_op(),
+            create_withdraw_tx(),
+        ],
+        (0, 1),
+        &[32, 100],
+        1_000_000.into(),
+        1_500_000.into(),
+        previous_block_root_hash,
+        42,
+    );
+
+    let mut builder = CommitmentBuilder::new(block.block_chunks_size);
+    let mut chunks_used = 0;
+    for op in block
+        .block_transactions
+        .iter()
+        .filter_map(ExecutedOperations::get_executed_op)
+    {
+        builder.push_op(op).unwrap();
+        chunks_used += op.chunks();
+    }
+    assert_eq!(builder.chunks_used(), chunks_used);
+    let commitment = builder.finalize(
+        block.block_number,
+        block.fee_account,
+        previous_block_root_hash,
+        block.new_root_hash,
+        block.timestamp,
+    );
+    assert_eq!(commitment, block.block_commitment);
+}
+
+#[test]
+fn test_commitment_builder_overflow() {
+    let withdraw = create_withdraw_tx();
+    let op = withdraw.get_executed_op().unwrap();
+    let mut builder = CommitmentBuilder::new(op.chunks() + 1);
+    builder.push_op(op).unwrap();
+
+    assert_eq!(
+        builder.push_op(op),
+        Err(BlockChunksOverflow {
+            op_chunks: op.chunks(),
+            free_chunks: 1,
+        })
+    );
+    assert_eq!(builder.chunks_used(), op.chunks());
+}
+
+#[test]
+fn test_min_valid_until() {
+    let withdraw_valid_until = |valid_until: u64| {
+        let mut tx = create_withdraw_tx();
+        if let ExecutedOperations::Tx(exec_tx) = &mut tx {
+            if let ZkSyncTx::Withdraw(withdraw) = &mut exec_tx.signed_tx.tx {
+                withdraw.time_range = Some(TimeRange::new(0, valid_until));
+            }
+        }
+        tx
+    };
+    let mut block = Block::new(
+        BlockNumber(0),
+        Fr::one(),
+        AccountId(0),
+        vec![
+            withdraw_valid_until(300),
+            create_full_exit_op(),
+            withdraw_valid_until(100),
+            withdraw_valid_until(200),
+        ],
+        (0, 0),
+        100,
+        1_000_000.into(),
+        1_500_000.into(),
+        H256::default(),
+        0,
+    );
+    assert_eq!(block.min_valid_until(), Some(100));
+
+    block.block_transactions = vec![create_full_exit_op()];
+    assert_eq!(block.min_valid_until(), None);
+}
+
 #[test]
 fn test_get_eth_witness_data() {
     let operations = vec![
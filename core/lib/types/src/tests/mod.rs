@@ -1,3 +1,4 @@
 mod block;
 mod hardcoded;
+mod operations;
 pub mod utils;
@@ -0,0 +1,885 @@
+use num::BigUint;
+use std::str::FromStr;
+use zksync_basic_types::{AccountId, Address, Nonce, TokenId};
+use zksync_crypto::params::{CHUNK_BYTES, NFT_STORAGE_ACCOUNT_ID};
+
+use crate::operations::{
+    error::{
+        ChangePubkeyOpError, CloseOpError, DepositOpError, ForcedExitOpError, FramedOpDecodeError,
+        FullExitOpError, MintNFTOpError, NoopOpError, OpFromTxError, PublicDataDecodeError,
+        SwapOpError, TransferOpError, UnexpectedOperationType, WithdrawNFTOpError, WithdrawOpError,
+    },
+    *,
+};
+use crate::{
+    priority_ops::{Deposit, FullExit},
+    tx::ChangePubKey,
+    Account, Close, ForcedExit, MintNFT, Order, PubKeyHash, Swap, Transfer, Withdraw, WithdrawNFT,
+    ZkSyncPriorityOp, ZkSyncTx, H256,
+};
+
+fn transfer_op() -> TransferOp {
+    let tx = Transfer::new(
+        AccountId(1),
+        Address::from_str("2a0a81e257a2f5d6ed4f07b81dbda09f107bd026").unwrap(),
+        Address::from_str("21abaed8712072e918632259780e587698ef58da").unwrap(),
+        TokenId(2),
+        BigUint::from(100u32),
+        BigUint::from(10u32),
+        Nonce(3),
+        Default::default(),
+        None,
+    );
+    TransferOp {
+        tx,
+        from: AccountId(1),
+        to: AccountId(2),
+    }
+}
+
+fn deposit_op() -> DepositOp {
+    DepositOp {
+        priority_op: Deposit {
+            from: Address::from_str("2a0a81e257a2f5d6ed4f07b81dbda09f107bd026").unwrap(),
+            to: Address::from_str("21abaed8712072e918632259780e587698ef58da").unwrap(),
+            token: TokenId(2),
+            amount: BigUint::from(500u32),
+        },
+        account_id: AccountId(4),
+    }
+}
+
+fn withdraw_op() -> WithdrawOp {
+    WithdrawOp {
+        tx: Withdraw::new(
+            AccountId(1),
+            Address::from_str("2a0a81e257a2f5d6ed4f07b81dbda09f107bd026").unwrap(),
+            Address::from_str("21abaed8712072e918632259780e587698ef58da").unwrap(),
+            TokenId(2),
+            BigUint::from(100u32),
+            BigUint::from(10u32),
+            Nonce(3),
+            Default::default(),
+            None,
+        ),
+        account_id: AccountId(1),
+    }
+}
+
+#[test]
+fn fee() {
+    let transfer = ZkSyncOp::from(transfer_op());
+    assert_eq!(transfer.fee(), Some(BigUint::from(10u32)));
+
+    let deposit = ZkSyncOp::from(deposit_op());
+    assert_eq!(deposit.fee(), None);
+}
+
+#[test]
+fn amount_encoding() {
+    assert_eq!(
+        ZkSyncOp::from(deposit_op()).amount_encoding(),
+        Some(AmountEncoding::Raw128)
+    );
+    assert_eq!(
+        ZkSyncOp::from(withdraw_op()).amount_encoding(),
+        Some(AmountEncoding::Raw128)
+    );
+    assert_eq!(
+        ZkSyncOp::from(transfer_op()).amount_encoding(),
+        Some(AmountEncoding::PackedFloat)
+    );
+    assert_eq!(ZkSyncOp::from(NoopOp::new()).amount_encoding(), None);
+}
+
+#[test]
+fn recipient_address() {
+    let withdraw = ZkSyncOp::from(withdraw_op());
+    assert_eq!(
+        withdraw.recipient_address(),
+        Some(Address::from_str("21abaed8712072e918632259780e587698ef58da").unwrap())
+    );
+
+    let transfer = ZkSyncOp::from(transfer_op());
+    assert_eq!(transfer.recipient_address(), None);
+}
+
+#[test]
+fn to_summary_json() {
+    let summary = ZkSyncOp::from(deposit_op()).to_summary_json();
+    assert_eq!(summary["type"], "Deposit");
+    assert_eq!(summary["op_code"], DepositOp::OP_CODE);
+    assert_eq!(summary["chunks"], DepositOp::CHUNKS);
+    assert_eq!(summary["accounts"], serde_json::json!([4]));
+    assert_eq!(
+        summary["amounts"],
+        serde_json::json!([{ "token": 2, "amount": "500" }])
+    );
+    assert!(summary["fee"].is_null());
+
+    let summary = ZkSyncOp::from(withdraw_op()).to_summary_json();
+    assert_eq!(summary["type"], "Withdraw");
+    assert_eq!(summary["fee"], "10");
+    assert_eq!(
+        summary["recipient_address"],
+        "0x21abaed8712072e918632259780e587698ef58da"
+    );
+
+    // The type is the same as the serialization tag.
+    let ops = vec![
+        ZkSyncOp::from(deposit_op()),
+        ZkSyncOp::from(transfer_op()),
+        ZkSyncOp::from(withdraw_op()),
+        ZkSyncOp::from(NoopOp {}),
+    ];
+    for op in ops {
+        let summary = op.to_summary_json();
+        assert_eq!(summary["type"], serde_json::to_value(&op).unwrap()["type"]);
+        assert_eq!(summary["op_code"], op.public_data()[0]);
+    }
+}
+
+#[test]
+fn l1_outflow() {
+    let withdraw = ZkSyncOp::from(withdraw_op());
+    let failed_full_exit = ZkSyncOp::from(FullExitOp {
+        priority_op: FullExit {
+            account_id: AccountId(4),
+            eth_address: Address::from_str("21abaed8712072e918632259780e587698ef58da").unwrap(),
+            token: TokenId(5),
+            is_legacy: false,
+        },
+        withdraw_amount: None,
+        creator_account_id: None,
+        creator_address: None,
+        serial_id: None,
+        content_hash: None,
+    });
+    assert!(withdraw.is_withdrawal_to_l1());
+    assert!(!failed_full_exit.is_withdrawal_to_l1());
+    assert!(!ZkSyncOp::from(transfer_op()).is_withdrawal_to_l1());
+
+    let ops = vec![withdraw, failed_full_exit, ZkSyncOp::from(transfer_op())];
+    let outflow = l1_outflow(&ops);
+    assert_eq!(outflow.len(), 1);
+    assert_eq!(outflow[&TokenId(2)], BigUint::from(100u32));
+}
+
+#[test]
+fn noops() {
+    let noops = ZkSyncOp::noops(3);
+    assert_eq!(noops.len(), 3);
+    assert!(noops
+        .iter()
+        .all(|op| matches!(op, ZkSyncOp::Noop(_)) && op.chunks() == 1));
+
+    let pubdata: Vec<u8> = noops.iter().flat_map(ZkSyncOp::public_data).collect();
+    assert_eq!(pubdata, vec![0u8; 3 * CHUNK_BYTES]);
+}
+
+#[test]
+fn transfer_new_validated() {
+    let op = transfer_op();
+    assert!(TransferOp::new_validated(op.tx.clone(), op.from, op.to).is_ok());
+
+    let mut tx = op.tx;
+    tx.fee = BigUint::from(1_234_123_424u32);
+    assert_eq!(
+        TransferOp::new_validated(tx, op.from, op.to).unwrap_err(),
+        TransferOpError::FeeNotPackable
+    );
+}
+
+#[test]
+fn parse_pubdata_blob() {
+    let ops = vec![
+        ZkSyncOp::from(deposit_op()),
+        ZkSyncOp::from(transfer_op()),
+        ZkSyncOp::from(NoopOp {}),
+    ];
+    let blob: Vec<u8> = ops.iter().flat_map(ZkSyncOp::public_data).collect();
+
+    let decoded = ZkSyncOp::parse_pubdata_blob(&blob).unwrap();
+    assert_eq!(decoded.len(), ops.len());
+    for (op, decoded) in ops.iter().zip(decoded.iter()) {
+        assert_eq!(op.public_data(), decoded.public_data());
+    }
+
+    assert_eq!(
+        ZkSyncOp::parse_pubdata_blob(&blob[..blob.len() - CHUNK_BYTES - 1]).unwrap_err(),
+        PublicDataDecodeError::TruncatedData
+    );
+}
+
+#[test]
+fn validate_and_decode_pubdata() {
+    let ops = vec![
+        ZkSyncOp::from(deposit_op()),
+        ZkSyncOp::from(transfer_op()),
+        ZkSyncOp::from(NoopOp {}),
+    ];
+    let blob: Vec<u8> = ops.iter().flat_map(ZkSyncOp::public_data).collect();
+    let chunks = ops.iter().map(ZkSyncOp::chunks).sum::<usize>();
+
+    let decoded = ZkSyncOp::validate_and_decode_pubdata(&blob, chunks).unwrap();
+    assert_eq!(decoded.len(), ops.len());
+
+    // The blob is extended with a noop, so it doesn't match the declared length anymore.
+    let mut extended_blob = blob.clone();
+    extended_blob.extend(ZkSyncOp::from(NoopOp {}).public_data());
+    assert_eq!(
+        ZkSyncOp::validate_and_decode_pubdata(&extended_blob, chunks).unwrap_err(),
+        PublicDataDecodeError::ChunksCountMismatch {
+            declared: chunks,
+            actual: chunks + 1,
+        }
+    );
+
+    // The last operation is cut off at its boundary.
+    let truncated_blob = &blob[..blob.len() - CHUNK_BYTES];
+    assert_eq!(
+        ZkSyncOp::validate_and_decode_pubdata(truncated_blob, chunks).unwrap_err(),
+        PublicDataDecodeError::ChunksCountMismatch {
+            declared: chunks,
+            actual: chunks - 1,
+        }
+    );
+}
+
+#[test]
+fn pubdata_checksum() {
+    let op = ZkSyncOp::from(transfer_op());
+    let checksum = op.pubdata_checksum();
+    assert!(op.verify_against_checksum(checksum));
+
+    let public_data = op.public_data();
+    for index in 0..public_data.len() {
+        let mut corrupted = public_data.clone();
+        corrupted[index] ^= 1;
+        assert_ne!(crc32fast::hash(&corrupted), checksum);
+    }
+    // Corrupted data that is still a valid operation is detected as well.
+    let mut corrupted_op = transfer_op();
+    corrupted_op.to = AccountId(*corrupted_op.to + 1);
+    assert!(!ZkSyncOp::from(corrupted_op).verify_against_checksum(checksum));
+}
+
+#[test]
+fn resulting_account() {
+    let deposit = ZkSyncOp::from(deposit_op());
+    let account = resulting_account(&deposit, &Account::default()).unwrap();
+    let priority_op = &deposit_op().priority_op;
+    assert_eq!(account.address, priority_op.to);
+    assert_eq!(account.get_balance(priority_op.token), priority_op.amount);
+    assert_eq!(account.nonce, Nonce(0));
+
+    // The transfer sender pays both the amount and the fee.
+    let transfer = transfer_op();
+    let mut sender = Account::default_with_address(&Address::repeat_byte(0x11));
+    sender.set_balance(transfer.tx.token, BigUint::from(1000u32));
+    let account = resulting_account(&ZkSyncOp::from(transfer.clone()), &sender).unwrap();
+    assert_eq!(
+        account.get_balance(transfer.tx.token),
+        BigUint::from(1000u32) - &transfer.tx.amount - &transfer.tx.fee
+    );
+    assert_eq!(account.nonce, Nonce(1));
+
+    assert!(resulting_account(&ZkSyncOp::from(transfer), &Account::default()).is_none());
+    assert!(resulting_account(&ZkSyncOp::from(NoopOp {}), &Account::default()).is_none());
+}
+
+/// Returns an operation of every type.
+fn sample_ops() -> Vec<ZkSyncOp> {
+    let address = Address::from_str("21abaed8712072e918632259780e587698ef58da").unwrap();
+    let order = |account_id, token_sell, token_buy| Order {
+        account_id,
+        recipient_address: address,
+        nonce: Nonce(0),
+        token_buy,
+        token_sell,
+        price: (BigUint::from(1u32), BigUint::from(2u32)),
+        amount: BigUint::from(100u32),
+        time_range: Default::default(),
+        signature: Default::default(),
+    };
+
+    vec![
+        ZkSyncOp::from(NoopOp {}),
+        ZkSyncOp::from(deposit_op()),
+        ZkSyncOp::from(transfer_op()),
+        ZkSyncOp::from(TransferToNewOp {
+            tx: transfer_op().tx,
+            from: AccountId(1),
+            to: AccountId(5),
+        }),
+        ZkSyncOp::from(withdraw_op()),
+        ZkSyncOp::from(CloseOp {
+            tx: Close {
+                account: address,
+                nonce: Nonce(0),
+                signature: Default::default(),
+                time_range: Default::default(),
+            },
+            account_id: AccountId(3),
+        }),
+        ZkSyncOp::from(FullExitOp {
+            priority_op: FullExit {
+                account_id: AccountId(4),
+                eth_address: address,
+                token: TokenId(5),
+                is_legacy: false,
+            },
+            withdraw_amount: Some(BigUint::from(300u32).into()),
+            creator_account_id: None,
+            creator_address: None,
+            serial_id: None,
+            content_hash: None,
+        }),
+        ZkSyncOp::from(ChangePubKeyOp {
+            tx: ChangePubKey::new(
+                AccountId(3),
+                address,
+                PubKeyHash::default(),
+                TokenId(2),
+                BigUint::from(10u32),
+                Nonce(1),
+                Default::default(),
+                None,
+                None,
+                None,
+            ),
+            account_id: AccountId(3),
+        }),
+        ZkSyncOp::from(ForcedExitOp {
+            tx: ForcedExit::new(
+                AccountId(1),
+                address,
+                TokenId(2),
+                BigUint::from(10u32),
+                Nonce(3),
+                Default::default(),
+                None,
+            ),
+            target_account_id: AccountId(6),
+            withdraw_amount: Some(BigUint::from(100u32).into()),
+        }),
+        ZkSyncOp::from(MintNFTOp {
+            tx: MintNFT::new(
+                AccountId(1),
+                address,
+                H256::repeat_byte(0x11),
+                address,
+                BigUint::from(10u32),
+                TokenId(2),
+                Nonce(3),
+                None,
+            ),
+            creator_account_id: AccountId(1),
+            recipient_account_id: AccountId(2),
+        }),
+        ZkSyncOp::from(WithdrawNFTOp {
+            tx: WithdrawNFT::new(
+                AccountId(1),
+                address,
+                address,
+                TokenId(70_000),
+                TokenId(2),
+                BigUint::from(10u32),
+                Nonce(3),
+                Default::default(),
+                None,
+            ),
+            creator_id: AccountId(7),
+            creator_address: address,
+            serial_id: 1,
+            content_hash: H256::repeat_byte(0x22),
+        }),
+        ZkSyncOp::from(SwapOp {
+            tx: Swap::new(
+                AccountId(5),
+                address,
+                Nonce(1),
+                (
+                    order(AccountId(1), TokenId(2), TokenId(3)),
+                    order(AccountId(2), TokenId(3), TokenId(2)),
+                ),
+                (BigUint::from(100u32), BigUint::from(200u32)),
+                BigUint::from(10u32),
+                TokenId(2),
+                None,
+            ),
+            submitter: AccountId(5),
+            accounts: (AccountId(1), AccountId(2)),
+            recipients: (AccountId(3), AccountId(4)),
+        }),
+    ]
+}
+
+#[test]
+fn public_data_round_trip() {
+    for op in sample_ops() {
+        let pubdata = op.public_data();
+        let decoded = ZkSyncOp::from_public_data(&pubdata)
+            .unwrap_or_else(|err| panic!("Failed to decode opcode {:#04x}: {}", pubdata[0], err));
+        assert_eq!(
+            std::mem::discriminant(&decoded),
+            std::mem::discriminant(&op),
+            "Variant mismatch, opcode {:#04x}",
+            pubdata[0]
+        );
+        assert_eq!(
+            decoded.public_data(),
+            pubdata,
+            "Pubdata mismatch, opcode {:#04x}",
+            pubdata[0]
+        );
+    }
+}
+
+#[test]
+fn byte_size() {
+    for op in sample_ops() {
+        assert_eq!(op.byte_size(), op.public_data().len());
+    }
+}
+
+#[test]
+fn field_layout() {
+    let op = deposit_op();
+    let pub_data = op.get_public_data();
+    let layout = ZkSyncOp::field_layout(DepositOp::OP_CODE).unwrap();
+    let field = |name: &str| {
+        let (_, offset, length) = layout
+            .iter()
+            .find(|(field, _, _)| field == name)
+            .unwrap_or_else(|| panic!("No field {} in the deposit layout", name));
+        &pub_data[*offset..*offset + *length]
+    };
+
+    assert_eq!(field("op_code"), [DepositOp::OP_CODE]);
+    assert_eq!(field("account_id"), op.account_id.to_be_bytes());
+    assert_eq!(field("token"), op.priority_op.token.to_be_bytes());
+    assert_eq!(field("amount"), 500u128.to_be_bytes());
+    assert_eq!(field("address"), op.priority_op.to.as_bytes());
+
+    // Fields are contiguous and fit into the operation chunks.
+    for op in sample_ops() {
+        let layout = ZkSyncOp::field_layout(op.public_data()[0]).unwrap();
+        let mut end = 0;
+        for (_, offset, length) in layout {
+            assert_eq!(offset, end);
+            end += length;
+        }
+        assert!(end <= op.byte_size());
+    }
+
+    assert_eq!(ZkSyncOp::field_layout(0xff), Err(UnexpectedOperationType()));
+}
+
+#[test]
+fn tokens_in_block() {
+    let mut other_token_transfer = transfer_op();
+    other_token_transfer.tx.token = TokenId(7);
+    let ops = vec![
+        ZkSyncOp::from(deposit_op()),
+        ZkSyncOp::from(transfer_op()),
+        ZkSyncOp::from(other_token_transfer),
+        ZkSyncOp::from(NoopOp {}),
+    ];
+
+    let tokens = tokens_in_block(&ops);
+    assert_eq!(
+        tokens.into_iter().collect::<Vec<_>>(),
+        vec![TokenId(2), TokenId(7)]
+    );
+}
+
+#[test]
+fn read_from_stream() {
+    let ops = vec![ZkSyncOp::from(deposit_op()), ZkSyncOp::from(transfer_op())];
+    let blob: Vec<u8> = ops.iter().flat_map(ZkSyncOp::public_data).collect();
+
+    let mut reader = std::io::Cursor::new(&blob);
+    for op in &ops {
+        let decoded = ZkSyncOp::read_from(&mut reader)
+            .unwrap()
+            .expect("Operation should be read");
+        assert_eq!(decoded.public_data(), op.public_data());
+    }
+    assert!(ZkSyncOp::read_from(&mut reader).unwrap().is_none());
+
+    let mut truncated = std::io::Cursor::new(&blob[..blob.len() - 1]);
+    ZkSyncOp::read_from(&mut truncated).unwrap();
+    assert_eq!(
+        ZkSyncOp::read_from(&mut truncated).unwrap_err(),
+        PublicDataDecodeError::TruncatedData
+    );
+}
+
+#[test]
+fn priority_op_bundle() {
+    let (priority_op, account_id) = ZkSyncOp::from(deposit_op()).priority_op_bundle().unwrap();
+    assert!(matches!(
+        priority_op,
+        ZkSyncPriorityOp::Deposit(deposit) if deposit.amount == BigUint::from(500u32)
+    ));
+    assert_eq!(account_id, AccountId(4));
+
+    let full_exit = ZkSyncOp::from(FullExitOp {
+        priority_op: FullExit {
+            account_id: AccountId(7),
+            eth_address: Address::from_str("21abaed8712072e918632259780e587698ef58da").unwrap(),
+            token: TokenId(5),
+            is_legacy: false,
+        },
+        withdraw_amount: None,
+        creator_account_id: None,
+        creator_address: None,
+        serial_id: None,
+        content_hash: None,
+    });
+    let (priority_op, account_id) = full_exit.priority_op_bundle().unwrap();
+    assert!(matches!(
+        priority_op,
+        ZkSyncPriorityOp::FullExit(full_exit) if full_exit.token == TokenId(5)
+    ));
+    assert_eq!(account_id, AccountId(7));
+
+    assert!(ZkSyncOp::from(transfer_op()).priority_op_bundle().is_none());
+}
+
+#[test]
+fn withdrawal_record() {
+    let withdraw = ZkSyncOp::from(withdraw_op());
+    let record = WithdrawalRecord::from_bytes(&withdraw.withdrawal_data().unwrap()).unwrap();
+    assert_eq!(
+        record,
+        WithdrawalRecord {
+            add_to_queue: true,
+            to: Address::from_str("21abaed8712072e918632259780e587698ef58da").unwrap(),
+            token: TokenId(2),
+            amount: BigUint::from(100u32),
+        }
+    );
+
+    let full_exit = ZkSyncOp::from(FullExitOp {
+        priority_op: FullExit {
+            account_id: AccountId(4),
+            eth_address: Address::from_str("21abaed8712072e918632259780e587698ef58da").unwrap(),
+            token: TokenId(5),
+            is_legacy: false,
+        },
+        withdraw_amount: Some(BigUint::from(300u32).into()),
+        creator_account_id: None,
+        creator_address: None,
+        serial_id: None,
+        content_hash: None,
+    });
+    let record = WithdrawalRecord::from_bytes(&full_exit.withdrawal_data().unwrap()).unwrap();
+    assert!(!record.add_to_queue);
+    assert_eq!(record.token, TokenId(5));
+    assert_eq!(record.amount, BigUint::from(300u32));
+
+    assert_eq!(WithdrawalRecord::from_bytes(&[1; 10]), None);
+}
+
+#[test]
+fn withdrawal_totals_do_not_overflow() {
+    const RECORDS_COUNT: u32 = 1000;
+    let amount = u128::MAX - 1;
+    let records = (0..RECORDS_COUNT)
+        .map(|i| {
+            let token = TokenId(i % 2);
+            let mut bytes = vec![1];
+            bytes.extend_from_slice(Address::repeat_byte(0x11).as_bytes());
+            bytes.extend_from_slice(&token.0.to_be_bytes());
+            bytes.extend_from_slice(&amount.to_be_bytes());
+            WithdrawalRecord::from_bytes(&bytes).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let totals = withdrawal_totals(&records);
+    let expected = BigUint::from(amount) * BigUint::from(RECORDS_COUNT / 2);
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals[&TokenId(0)], expected);
+    assert_eq!(totals[&TokenId(1)], expected);
+}
+
+#[test]
+fn framed_round_trip() {
+    let ops = vec![
+        ZkSyncOp::from(transfer_op()),
+        ZkSyncOp::from(deposit_op()),
+        ZkSyncOp::from(withdraw_op()),
+    ];
+    let mut buffer = Vec::new();
+    for op in &ops {
+        op.write_framed(&mut buffer).unwrap();
+    }
+
+    let mut reader = std::io::Cursor::new(&buffer);
+    for op in &ops {
+        let decoded = ZkSyncOp::read_framed(&mut reader)
+            .unwrap()
+            .expect("Operation should be read");
+        assert_eq!(decoded.op_code(), op.op_code());
+        assert_eq!(decoded.public_data(), op.public_data());
+    }
+    assert!(ZkSyncOp::read_framed(&mut reader).unwrap().is_none());
+
+    let mut truncated = std::io::Cursor::new(&buffer[..buffer.len() - 1]);
+    ZkSyncOp::read_framed(&mut truncated).unwrap();
+    ZkSyncOp::read_framed(&mut truncated).unwrap();
+    assert_eq!(
+        ZkSyncOp::read_framed(&mut truncated).unwrap_err(),
+        FramedOpDecodeError::TruncatedData
+    );
+}
+
+#[test]
+fn framed_too_large() {
+    let mut frame = vec![TransferOp::OP_CODE];
+    frame.extend_from_slice(&u32::MAX.to_be_bytes());
+    assert_eq!(
+        ZkSyncOp::read_framed(&mut frame.as_slice()).unwrap_err(),
+        FramedOpDecodeError::FrameTooLarge(u32::MAX as usize)
+    );
+}
+
+#[test]
+fn op_code() {
+    for op in sample_ops() {
+        assert_eq!(op.op_code(), op.public_data()[0]);
+    }
+}
+
+#[test]
+fn reconstruction_from_pubdata() {
+    let deposit = ZkSyncOp::from(deposit_op());
+    assert!(deposit.is_fully_reconstructable_from_pubdata());
+    let restored = ZkSyncOp::from_public_data(&deposit.public_data()).unwrap();
+    let restored = match restored {
+        ZkSyncOp::Deposit(op) => op,
+        _ => panic!("Deposit expected"),
+    };
+    assert_eq!(restored.account_id, deposit_op().account_id);
+    assert_eq!(restored.priority_op.amount, deposit_op().priority_op.amount);
+    assert_eq!(restored.priority_op.to, deposit_op().priority_op.to);
+
+    let transfer = ZkSyncOp::from(transfer_op());
+    assert!(!transfer.is_fully_reconstructable_from_pubdata());
+    let restored = ZkSyncOp::from_public_data(&transfer.public_data()).unwrap();
+    let restored = match restored {
+        ZkSyncOp::Transfer(op) => op,
+        _ => panic!("Transfer expected"),
+    };
+    assert_eq!(restored.tx.amount, transfer_op().tx.amount);
+    assert_ne!(restored.tx.nonce, transfer_op().tx.nonce);
+
+    // The transaction of the restored operation is incomplete.
+    let restored_tx = ZkSyncOp::from(*restored).try_get_tx().unwrap();
+    assert_ne!(restored_tx.hash(), transfer.try_get_tx().unwrap().hash());
+}
+
+#[test]
+fn close_op_is_not_a_tx() {
+    let mut pubdata = vec![CloseOp::OP_CODE];
+    pubdata.extend_from_slice(&AccountId(5).to_be_bytes());
+    pubdata.resize(CloseOp::CHUNKS * CHUNK_BYTES, 0x00);
+    let close = ZkSyncOp::from_public_data(&pubdata).unwrap();
+    assert!(matches!(close, ZkSyncOp::Close(_)));
+
+    assert_eq!(close.try_get_tx().unwrap_err(), UnexpectedOperationType());
+    assert_eq!(
+        close.try_get_priority_op().unwrap_err(),
+        UnexpectedOperationType()
+    );
+}
+
+#[test]
+fn diff_op_sequences() {
+    let ops = vec![ZkSyncOp::from(deposit_op()), ZkSyncOp::from(transfer_op())];
+    assert_eq!(diff_op_sequences(&ops, &ops), None);
+
+    let mut other_amount_transfer = transfer_op();
+    other_amount_transfer.tx.amount = BigUint::from(200u32);
+    let other_ops = vec![
+        ZkSyncOp::from(deposit_op()),
+        ZkSyncOp::from(other_amount_transfer),
+    ];
+    let diff = diff_op_sequences(&ops, &other_ops).expect("Sequences differ");
+    assert_eq!(diff.index, 1);
+
+    let diff = diff_op_sequences(&ops, &ops[..1]).expect("Sequences differ");
+    assert_eq!(diff.index, 1);
+}
+
+#[test]
+fn reserved_account() {
+    assert!(!is_reserved_account(AccountId(0)));
+    assert!(is_reserved_account(NFT_STORAGE_ACCOUNT_ID));
+
+    // Account 0 belongs to the operator, so it's treated as any other account.
+    let mut deposit = deposit_op();
+    deposit.account_id = AccountId(0);
+    let decoded = DepositOp::from_public_data(&deposit.get_public_data()).unwrap();
+    assert_eq!(decoded.account_id, AccountId(0));
+
+    let mut withdraw = withdraw_op();
+    withdraw.account_id = AccountId(0);
+    let decoded = WithdrawOp::from_public_data(&withdraw.get_public_data()).unwrap();
+    assert_eq!(decoded.account_id, AccountId(0));
+
+    // Nobody can sign a withdrawal from the reserved account.
+    withdraw.account_id = NFT_STORAGE_ACCOUNT_ID;
+    assert_eq!(
+        WithdrawOp::from_public_data(&withdraw.get_public_data()).unwrap_err(),
+        WithdrawOpError::ReservedAccount
+    );
+}
+
+#[test]
+fn non_zero_padding_is_rejected() {
+    // `TransferOp` is not listed since its fields fill all of its chunks.
+    let cases: Vec<(u8, usize, PublicDataDecodeError)> = vec![
+        (
+            NoopOp::OP_CODE,
+            NoopOp::CHUNKS,
+            NoopOpError::IncorrectPubdata.into(),
+        ),
+        (
+            DepositOp::OP_CODE,
+            DepositOp::CHUNKS,
+            DepositOpError::NonZeroPadding.into(),
+        ),
+        (
+            TransferToNewOp::OP_CODE,
+            TransferToNewOp::CHUNKS,
+            TransferOpError::NonZeroPadding.into(),
+        ),
+        (
+            WithdrawOp::OP_CODE,
+            WithdrawOp::CHUNKS,
+            WithdrawOpError::NonZeroPadding.into(),
+        ),
+        (
+            FullExitOp::OP_CODE,
+            FullExitOp::CHUNKS,
+            FullExitOpError::NonZeroPadding.into(),
+        ),
+        (
+            ChangePubKeyOp::OP_CODE,
+            ChangePubKeyOp::CHUNKS,
+            ChangePubkeyOpError::NonZeroPadding.into(),
+        ),
+        (
+            ForcedExitOp::OP_CODE,
+            ForcedExitOp::CHUNKS,
+            ForcedExitOpError::NonZeroPadding.into(),
+        ),
+        (
+            SwapOp::OP_CODE,
+            SwapOp::CHUNKS,
+            SwapOpError::NonZeroPadding.into(),
+        ),
+        (
+            MintNFTOp::OP_CODE,
+            MintNFTOp::CHUNKS,
+            MintNFTOpError::NonZeroPadding.into(),
+        ),
+        (
+            WithdrawNFTOp::OP_CODE,
+            WithdrawNFTOp::CHUNKS,
+            WithdrawNFTOpError::NonZeroPadding.into(),
+        ),
+        (
+            CloseOp::OP_CODE,
+            CloseOp::CHUNKS,
+            CloseOpError::NonZeroPadding.into(),
+        ),
+    ];
+
+    for (op_code, chunks, expected_error) in cases {
+        let mut pubdata = vec![op_code];
+        pubdata.resize(chunks * CHUNK_BYTES, 0x00);
+        assert!(
+            ZkSyncOp::from_public_data(&pubdata).is_ok(),
+            "Zero-padded pubdata should be decoded, opcode {:#04x}",
+            op_code
+        );
+
+        *pubdata.last_mut().unwrap() = 0x01;
+        assert_eq!(
+            ZkSyncOp::from_public_data(&pubdata).unwrap_err(),
+            expected_error,
+            "Non-zero padding should be rejected, opcode {:#04x}",
+            op_code
+        );
+    }
+}
+
+#[test]
+fn from_tx() {
+    let tx = ZkSyncTx::from(transfer_op().tx);
+
+    let op = ZkSyncOp::from_tx(&tx, AccountId(1), Some(AccountId(2)), false).unwrap();
+    match op {
+        ZkSyncOp::Transfer(op) => {
+            assert_eq!(op.from, AccountId(1));
+            assert_eq!(op.to, AccountId(2));
+        }
+        _ => panic!("Expected transfer, got {:?}", op),
+    }
+
+    let op = ZkSyncOp::from_tx(&tx, AccountId(1), Some(AccountId(3)), true).unwrap();
+    match op {
+        ZkSyncOp::TransferToNew(op) => {
+            assert_eq!(op.from, AccountId(1));
+            assert_eq!(op.to, AccountId(3));
+        }
+        _ => panic!("Expected transfer to new, got {:?}", op),
+    }
+    assert_eq!(
+        ZkSyncOp::from_tx(&tx, AccountId(1), None, false).unwrap_err(),
+        OpFromTxError::MissingRecipient
+    );
+
+    let withdraw = ZkSyncTx::from(withdraw_op().tx);
+    let op = ZkSyncOp::from_tx(&withdraw, AccountId(1), None, false).unwrap();
+    assert!(matches!(op, ZkSyncOp::Withdraw(ref op) if op.account_id == AccountId(1)));
+    assert_eq!(op.try_get_tx().unwrap().hash(), withdraw.hash());
+}
+
+#[test]
+fn account_creation() {
+    let TransferOp { tx, from, to } = transfer_op();
+    let transfer_to_new = ZkSyncOp::from(TransferToNewOp { tx, from, to });
+    assert!(transfer_to_new.is_account_creating());
+
+    assert!(!ZkSyncOp::from(transfer_op()).is_account_creating());
+    assert!(!ZkSyncOp::from(deposit_op()).is_account_creating());
+}
+
+#[test]
+fn fee_payer() {
+    assert_eq!(
+        ZkSyncOp::from(transfer_op()).fee_payer(),
+        Some(AccountId(1))
+    );
+    assert_eq!(ZkSyncOp::from(deposit_op()).fee_payer(), None);
+
+    let forced_exit = sample_ops()
+        .into_iter()
+        .find(|op| matches!(op, ZkSyncOp::ForcedExit(_)))
+        .unwrap();
+    assert_eq!(
+        forced_exit.get_updated_account_ids(),
+        vec![AccountId(6), AccountId(1)]
+    );
+    assert_eq!(forced_exit.fee_payer(), Some(AccountId(1)));
+
+    // Every operation with a fee has a payer.
+    for op in sample_ops() {
+        assert_eq!(op.fee().is_some(), op.fee_payer().is_some(), "{:?}", op);
+    }
+}
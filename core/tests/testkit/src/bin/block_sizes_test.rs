@@ -229,9 +229,14 @@ async fn main() {
             proofs.push((proof.clone(), block_size));
         }
         let (vks, proof_data) = prepare_proof_data(&available_block_chunk_sizes, proofs);
-        let aggreagated_proof =
-            gen_aggregate_proof(vks, proof_data, &available_aggregated_proof_sizes, false)
-                .expect("Failed to generate aggreagated proof");
+        let aggreagated_proof = gen_aggregate_proof(
+            vks,
+            proof_data,
+            &available_aggregated_proof_sizes,
+            false,
+            true,
+        )
+        .expect("Failed to generate aggreagated proof");
 
         let proof_op = BlocksProofOperation {
             blocks: vec![block],
@@ -304,9 +309,14 @@ async fn main() {
         }
 
         let (vks, proof_data) = prepare_proof_data(&available_block_chunk_sizes, proofs);
-        let aggregated_proof =
-            gen_aggregate_proof(vks, proof_data, &available_aggregated_proof_sizes, false)
-                .expect("Failed to generate aggregated proof");
+        let aggregated_proof = gen_aggregate_proof(
+            vks,
+            proof_data,
+            &available_aggregated_proof_sizes,
+            false,
+            true,
+        )
+        .expect("Failed to generate aggregated proof");
 
         let proof_op = BlocksProofOperation {
             blocks,
@@ -55,10 +55,18 @@ export async function circuit(threads: number = 1, testName?: string, ...args: s
 
 export async function prover() {
     await utils.spawn('cargo test -p zksync_prover --release');
+    // Proof generation tests require the same setup and keys as the prover.
+    await utils.spawn('cargo test -p zksync_prover_utils --release -- --ignored');
 }
 
 export async function witness_generator() {
     await utils.spawn('cargo test -p zksync_witness_generator --release');
+    // Tests using the mock database still need the storage connection.
+    await runOnTestDb(
+        false,
+        'core/bin/zksync_witness_generator',
+        'cargo test --release -p zksync_witness_generator --features db_test --lib -- --nocapture --test-threads=1'
+    );
 }
 
 export async function js() {